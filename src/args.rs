@@ -0,0 +1,58 @@
+/************************************************************************************************/
+
+pub const ARG_VERBOSE_LONG: &str = "verbose";
+pub const ARG_VERBOSE_NAME: &str = "verbose";
+pub const ARG_VERBOSE_SHORT: &str = "v";
+pub const ARG_SITE_NAME: &str = "site";
+pub const ARG_ENV_LONG: &str = "env";
+pub const ARG_ENV_NAME: &str = "env";
+pub const ARG_SITE_DIR_LONG: &str = "site-dir";
+pub const ARG_SITE_DIR_NAME: &str = "site-dir";
+pub const ARG_CONFIG_LONG: &str = "config";
+pub const ARG_CONFIG_NAME: &str = "config";
+pub const ARG_CONFIG_SHORT: &str = "c";
+pub const ARG_INPUT_LONG: &str = "input";
+pub const ARG_INPUT_NAME: &str = "input";
+pub const ARG_INPUT_SHORT: &str = "i";
+pub const ARG_OUTPUT_LONG: &str = "output";
+pub const ARG_OUTPUT_NAME: &str = "output";
+pub const ARG_OUTPUT_SHORT: &str = "o";
+pub const ARG_QUIET_LONG: &str = "quiet";
+pub const ARG_QUIET_NAME: &str = "quiet";
+pub const ARG_QUIET_SHORT: &str = "q";
+pub const ARG_LOG_LEVEL_LONG: &str = "log-level";
+pub const ARG_LOG_LEVEL_NAME: &str = "log-level";
+pub const ARG_TIMESTAMPS_LONG: &str = "timestamps";
+pub const ARG_TIMESTAMPS_NAME: &str = "timestamps";
+pub const ARG_TIMINGS_LONG: &str = "timings";
+pub const ARG_TIMINGS_NAME: &str = "timings";
+pub const ARG_COLOR_LONG: &str = "color";
+pub const ARG_COLOR_NAME: &str = "color";
+pub const ARG_LANG_LONG: &str = "lang";
+pub const ARG_LANG_NAME: &str = "lang";
+pub const ARG_JOBS_LONG: &str = "jobs";
+pub const ARG_JOBS_NAME: &str = "jobs";
+pub const ARG_JOBS_SHORT: &str = "j";
+pub const ARG_REPORT_LONG: &str = "report";
+pub const ARG_REPORT_NAME: &str = "report";
+pub const ARG_FORMAT_LONG: &str = "format";
+pub const ARG_FORMAT_NAME: &str = "format";
+pub const ARG_DRY_RUN_LONG: &str = "dry-run";
+pub const ARG_DRY_RUN_NAME: &str = "dry-run";
+pub const ARG_KEEP_LONG: &str = "keep";
+pub const ARG_KEEP_NAME: &str = "keep";
+
+pub const COMMAND_BUILD_NAME: &str = "build";
+pub const COMMAND_CHECK_NAME: &str = "check";
+pub const COMMAND_CLEAN_NAME: &str = "clean";
+pub const COMMAND_NEW_NAME: &str = "new";
+
+pub const ENV_LANG: &str = "LANG";
+pub const ENV_LC_ALL: &str = "LC_ALL";
+pub const ENV_LC_MESSAGES: &str = "LC_MESSAGES";
+pub const ENV_YASG_ENV: &str = "YASG_ENV";
+
+pub const FORMAT_JSON: &str = "json";
+pub const FORMAT_TEXT: &str = "text";
+
+/************************************************************************************************/
@@ -1,40 +1,87 @@
 /************************************************************************************************/
 
 use crate::config::SiteConfig;
+use crate::config::SymlinkPolicy;
 use crate::constants::*;
+use crate::data::load_data_files;
+use crate::error::ErrorKind;
 use crate::error::YasgError;
+use crate::hooks::HookPoint;
+use crate::ignore::IgnoreRules;
+use crate::report::BuildReport;
+use crate::templates::Templates;
 use crate::text::s;
 use crate::text::sr;
 use crate::text::Text::*;
 use crate::verbose::Verbose;
 use crate::yasg::YasgClass;
 use crate::yasg::YasgFile;
+use rayon::prelude::*;
 use std::collections::HashMap;
 use std::fs::copy;
 use std::fs::create_dir_all;
+use std::fs::read_link;
+use std::fs::remove_dir_all;
+use std::fs::remove_file;
+use std::fs::write;
+use std::fs::DirEntry;
+use std::fs::FileType;
+use std::io;
+use std::path::Path;
 use std::path::PathBuf;
+use std::time::Instant;
+use yaml_rust::Yaml;
 
 /************************************************************************************************/
 
-pub fn perform_build(verbose: &mut Verbose) -> Result<(), YasgError> {
+pub(crate) fn perform_build(
+    verbose: &mut Verbose,
+    mut config: SiteConfig,
+    report: &mut BuildReport,
+) -> Result<(), YasgError> {
     verbose.println(s(VerboseBuilding));
     verbose.increate_indent();
 
-    verbose.println(s(VerboseReadingSiteConfig));
+    let started = Instant::now();
+    config.prepare_output(verbose)?;
+    config.plugins.load(verbose)?;
+    write_build_marker(&config)?;
+    report.set_config(&config);
+    report.add_timing(REPORT_PHASE_CONFIG, started);
+
+    config
+        .hooks
+        .run(verbose, &config, HookPoint::PreBuild, None)?;
+
+    let started = Instant::now();
+    verbose.println(s(VerboseBuildingFileList));
     verbose.increate_indent();
-    let config = SiteConfig::read_from_yaml(verbose, true, true)?;
+    let file_list = build_file_list(verbose, &config)?;
+    report.add_timing(REPORT_PHASE_FILE_LIST, started);
     verbose.decrease_indent();
 
-    verbose.println(s(VerboseBuildingFileList));
+    config
+        .hooks
+        .run(verbose, &config, HookPoint::PostScan, None)?;
+
+    let started = Instant::now();
+    verbose.println(s(VerboseLoadingDataFiles));
     verbose.increate_indent();
-    let file_list = build_file_list(&config);
+    let data = load_data_files(verbose, &config).map_err(|e| e.kind(ErrorKind::Content))?;
+    report.add_timing(REPORT_PHASE_DATA, started);
     verbose.decrease_indent();
 
+    let started = Instant::now();
     verbose.println(s(VerboseProcessingFiles));
     verbose.increate_indent();
-    process_files(verbose, &config, &file_list);
+    process_files(verbose, &config, &file_list, &data, report)?;
+    report.add_timing(REPORT_PHASE_FILES, started);
     verbose.decrease_indent();
 
+    config
+        .hooks
+        .run(verbose, &config, HookPoint::PostBuild, None)?;
+
     verbose.decrease_indent();
     verbose.println(s(VerboseDone));
 
@@ -43,78 +90,343 @@ pub fn perform_build(verbose: &mut Verbose) -> Result<(), YasgError> {
 
 /************************************************************************************************/
 
-fn build_file_list(config: &SiteConfig) -> Vec<PathBuf> {
+pub(crate) fn build_file_list(
+    verbose: &mut Verbose,
+    config: &SiteConfig,
+) -> Result<Vec<PathBuf>, YasgError> {
+    let rules = IgnoreRules::load(config)?;
     let mut file_list = Vec::new();
+    let mut ancestors = vec![config.input.clone()];
+
+    scan_directory(
+        verbose,
+        config,
+        &rules,
+        &mut file_list,
+        &mut ancestors,
+        &config.input,
+        Path::new(""),
+    )?;
+    // a stable order keeps the build deterministic
+    file_list.sort();
+
+    Ok(file_list)
+}
+
+/************************************************************************************************/
+
+fn scan_directory(
+    verbose: &mut Verbose,
+    config: &SiteConfig,
+    rules: &IgnoreRules,
+    file_list: &mut Vec<PathBuf>,
+    ancestors: &mut Vec<PathBuf>,
+    dir: &Path,
+    relative: &Path,
+) -> Result<(), YasgError> {
+    let read_error = |e: std::io::Error| {
+        YasgError::new(format!("{}", e))
+            .add(sr(ErrorWhileReadingFile, &[&dir.to_string_lossy()]))
+            .kind(ErrorKind::Io)
+    };
 
-    scan_directory(config, &mut file_list, &config.input);
+    let mut entries = dir
+        .read_dir()
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(read_error)?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let entry_relative = relative.join(entry.file_name());
+
+        // the rest of the build shows paths to the user, so they have to be valid UTF-8
+        if entry.file_name().to_str().is_none() {
+            verbose.warning(&sr(
+                WarningNameNotUtf8,
+                &[&entry_relative.to_string_lossy()],
+            ));
+            continue;
+        }
+        let name = entry_relative.to_str().unwrap();
 
-    file_list
+        let file_type =
+            match entry_file_type(verbose, config.symlinks, &entry, name).map_err(read_error)? {
+                Some(file_type) => file_type,
+                None => continue,
+            };
+        let is_dir = file_type.is_dir();
+
+        // like git, nothing inside an excluded directory can be included again
+        if let Some((pattern, source)) = rules.excluded_by(&entry_relative, is_dir) {
+            verbose.println(&sr(VerboseExcluding, &[name, pattern, source]));
+        } else if is_dir {
+            let canonical = match enter_directory(verbose, config, &path, name, ancestors)
+                .map_err(read_error)?
+            {
+                Some(canonical) => canonical,
+                None => continue,
+            };
+            ancestors.push(canonical);
+            scan_directory(
+                verbose,
+                config,
+                rules,
+                file_list,
+                ancestors,
+                &path,
+                &entry_relative,
+            )?;
+            ancestors.pop();
+        } else if file_type.is_file() || file_type.is_symlink() {
+            file_list.push(path);
+        } else {
+            // pipes, sockets and devices would block or fail the build when read
+            verbose.warning(&sr(WarningSpecialFile, &[name]));
+        }
+    }
+
+    Ok(())
 }
 
 /************************************************************************************************/
 
-fn scan_directory(config: &SiteConfig, file_list: &mut Vec<PathBuf>, dir: &PathBuf) {
-    if dir.is_dir() {
-        for entry in dir.read_dir().unwrap() {
-            if let Ok(entry) = entry {
-                if entry.path().is_dir() {
-                    scan_directory(config, file_list, &entry.path());
-                } else {
-                    file_list.push(entry.path());
-                }
+pub(crate) fn entry_file_type(
+    verbose: &mut Verbose,
+    policy: SymlinkPolicy,
+    entry: &DirEntry,
+    name: &str,
+) -> std::io::Result<Option<FileType>> {
+    // a link that is copied as it is counts as a file, otherwise its target counts
+    let file_type = entry.file_type()?;
+    if !file_type.is_symlink() {
+        return Ok(Some(file_type));
+    }
+
+    match policy {
+        SymlinkPolicy::Follow => match entry.path().metadata() {
+            Ok(metadata) => Ok(Some(metadata.file_type())),
+            Err(_) => {
+                verbose.warning(&sr(WarningSymlinkDangling, &[name]));
+                Ok(None)
             }
+        },
+        SymlinkPolicy::Copy => Ok(Some(file_type)),
+        SymlinkPolicy::Skip => {
+            verbose.println(&sr(VerboseSkippingSymlink, &[name]));
+            Ok(None)
         }
     }
 }
 
 /************************************************************************************************/
 
-fn process_files(verbose: &mut Verbose, config: &SiteConfig, file_list: &[PathBuf]) {
-    let mut templates = HashMap::new();
+pub(crate) fn enter_directory(
+    verbose: &mut Verbose,
+    config: &SiteConfig,
+    path: &Path,
+    name: &str,
+    ancestors: &[PathBuf],
+) -> std::io::Result<Option<PathBuf>> {
+    // a link back to a directory that is being scanned would never end
+    let canonical = path.canonicalize()?;
+    if let Some(ancestor) = ancestors.iter().find(|a| **a == canonical) {
+        verbose.warning(&sr(
+            WarningSymlinkLoop,
+            &[name, &ancestor.to_string_lossy()],
+        ));
+        return Ok(None);
+    }
+
+    // a link can also reach the output, which would then be copied into itself
+    if canonical == config.output {
+        verbose.println(&sr(VerboseSkippingOutput, &[name]));
+        return Ok(None);
+    }
+
+    Ok(Some(canonical))
+}
+
+/************************************************************************************************/
+
+fn process_files(
+    verbose: &mut Verbose,
+    config: &SiteConfig,
+    file_list: &[PathBuf],
+    data: &Yaml,
+    report: &mut BuildReport,
+) -> Result<(), YasgError> {
+    let mut templates = Templates::default();
     let mut pages = Vec::new();
 
-    for path in file_list.iter() {
-        let extension = path.extension().unwrap();
+    // data files are only available to the templates
+    let (yasg_paths, asset_paths): (Vec<&PathBuf>, Vec<&PathBuf>) = file_list
+        .iter()
+        .filter(|path| !path.starts_with(&config.data))
+        .partition(|path| !is_copied_link(config, path) && YasgFile::has_front_matter(path));
+
+    // the work is done in parallel, the results are reported in the order of the file list
+    let copied: Vec<Result<PathBuf, YasgError>> = asset_paths
+        .par_iter()
+        .map(|path| copy_file(config, path))
+        .collect();
+    for result in copied {
+        let relative = result?;
+        verbose.println(&sr(VerboseCopying, &[relative.to_str().unwrap()]));
+        report.add_asset(&relative, &relative);
+    }
 
-        if extension.eq(EXTENSION_YASG) {
-            let yasg_file = YasgFile::parse(&config, path).unwrap();
+    let parsed: Vec<Result<YasgFile, YasgError>> = yasg_paths
+        .par_iter()
+        .map(|path| YasgFile::parse(config, path))
+        .collect();
+    for result in parsed {
+        let yasg_file = result?;
 
-            if yasg_file.class().is_some() {
-                match yasg_file.class().unwrap() {
-                    YasgClass::Template => {
-                        templates.insert(yasg_file.for_class().unwrap(), yasg_file);
-                    }
-                    YasgClass::Page => pages.push(yasg_file),
-                }
+        if yasg_file.class().is_some() {
+            match yasg_file.class().unwrap() {
+                YasgClass::Template => templates.add(yasg_file),
+                YasgClass::Page => pages.push(yasg_file),
+                YasgClass::Admonition => {}
             }
-        } else {
-            copy_file(verbose, config, path);
         }
     }
 
+    // plugins see every page that exists on disk and may add pages of their own
+    let existing: Vec<_> = pages.iter().map(YasgFile::plugin_data).collect();
+    for (plugin, path, source) in config.plugins.generate_pages(existing)? {
+        let page = YasgFile::parse_source(config, &config.input.join(&path), &source)?;
+        verbose.println(&sr(
+            VerboseGeneratedPage,
+            &[path.to_str().unwrap(), &plugin],
+        ));
+        if page.class() == Some(YasgClass::Page) {
+            pages.push(page);
+        }
+    }
+
+    check_output_paths(&asset_paths, &pages, config)?;
+
+    // pages can only be validated once the templates declaring their fields are known
+    templates.prepare(verbose, config)?;
+    for page in pages.iter() {
+        let template = templates.for_page(page)?;
+        page.validate(verbose, config, template)?;
+    }
+
     verbose.println(s(VerboseProcessingPages));
     verbose.increate_indent();
-    process_pages(verbose, config, &templates, &pages);
+    process_pages(verbose, config, &templates, &pages, data, report)?;
     verbose.decrease_indent();
+
+    Ok(())
 }
 
 /************************************************************************************************/
 
-fn copy_file(verbose: &mut Verbose, config: &SiteConfig, from_path: &PathBuf) {
+fn copy_file(config: &SiteConfig, from_path: &Path) -> Result<PathBuf, YasgError> {
     let relative = config.relative_to_input(from_path);
 
-    verbose.println(&sr(VerboseCopying, &[&relative.to_str().unwrap()]));
-
     let mut to = config.output.clone();
-    to.push(relative);
+    to.push(&relative);
 
     let to_dir = to.parent().unwrap();
 
+    // links to directories copied by a previous build are replaced as well, from the top down so
+    // nothing is ever removed through them, the files in them are copied in parallel so another
+    // one may have removed the link already
+    let parents: Vec<&Path> = relative.ancestors().skip(1).collect();
+    for parent in parents.into_iter().rev() {
+        let dir = config.output.join(parent);
+        if parent.as_os_str().is_empty() || !dir.is_symlink() {
+            continue;
+        }
+        match remove_file(&dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(YasgError::new(format!("{}", e))
+                    .add(sr(ErrorWriteFile, &[dir.to_str().unwrap()]))
+                    .kind(ErrorKind::Io));
+            }
+            _ => {}
+        }
+    }
+
     if !to_dir.exists() {
-        create_dir_all(to_dir).unwrap();
+        create_dir_all(to_dir).map_err(|e| {
+            YasgError::new(format!("{}", e))
+                .add(sr(ErrorWriteFile, &[to_dir.to_str().unwrap()]))
+                .kind(ErrorKind::Io)
+        })?;
+    }
+
+    // a link left by a previous build is replaced, never written through, and so is a directory
+    // or file left by following links before, as the link itself cannot be created over them
+    let link = is_copied_link(config, from_path);
+    let removed = match to.symlink_metadata() {
+        Ok(metadata) if metadata.is_dir() => remove_dir_all(&to),
+        Ok(metadata) if metadata.is_symlink() || link => remove_file(&to),
+        _ => Ok(()),
+    };
+    let copied = removed.and_then(|()| {
+        if link {
+            // the link itself is copied, its target is left as it is
+            read_link(from_path).and_then(|target| create_symlink(&target, &to))
+        } else {
+            copy(from_path, &to).map(|_| ())
+        }
+    });
+    copied.map_err(|e| {
+        YasgError::new(format!("{}", e))
+            .add(sr(ErrorWriteFile, &[to.to_str().unwrap()]))
+            .kind(ErrorKind::Io)
+    })?;
+
+    Ok(relative)
+}
+
+/************************************************************************************************/
+
+fn check_output_paths(
+    asset_paths: &[&PathBuf],
+    pages: &[YasgFile],
+    config: &SiteConfig,
+) -> Result<(), YasgError> {
+    // 'index.md' and 'index.yasg' both become 'index.html', only one of them can win
+    let mut outputs: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let sources = asset_paths
+        .iter()
+        .map(|path| {
+            let relative = config.relative_to_input(path);
+            (relative.clone(), relative)
+        })
+        .chain(
+            pages
+                .iter()
+                .map(|page| (page.output_path(), page.relative_path().clone())),
+        );
+
+    for (output, source) in sources {
+        if let Some(other) = outputs.get(&output) {
+            return Err(YasgError::new(sr(
+                ErrorDuplicateOutput,
+                &[
+                    other.to_str().unwrap(),
+                    source.to_str().unwrap(),
+                    output.to_str().unwrap(),
+                ],
+            ))
+            .kind(ErrorKind::Content));
+        }
+        outputs.insert(output, source);
     }
 
-    copy(from_path.to_str().unwrap(), to.to_str().unwrap()).unwrap();
+    Ok(())
+}
+
+/************************************************************************************************/
+
+fn is_copied_link(config: &SiteConfig, path: &Path) -> bool {
+    config.symlinks == SymlinkPolicy::Copy && path.is_symlink()
 }
 
 /************************************************************************************************/
@@ -122,19 +434,111 @@ fn copy_file(verbose: &mut Verbose, config: &SiteConfig, from_path: &PathBuf) {
 fn process_pages(
     verbose: &mut Verbose,
     config: &SiteConfig,
-    templates: &HashMap<YasgClass, YasgFile>,
+    templates: &Templates,
     pages: &[YasgFile],
-) {
+    data: &Yaml,
+    report: &mut BuildReport,
+) -> Result<(), YasgError> {
+    let admonition = templates.admonition()?;
+
+    // pages sharing a path apart from the language suffix are translations of each other
+    let mut translations: HashMap<&Path, Vec<&YasgFile>> = HashMap::new();
     for page in pages {
-        if let Some(class) = page.class() {
-            if let Some(template) = templates.get(&class) {
-                verbose.println(&sr(
-                    VerboseCompiling,
-                    &[&page.relative_path().to_str().unwrap()],
-                ));
-                page.compile(config, template);
-            }
+        let group = translations.entry(page.translation_path()).or_default();
+        if let Some(other) = group.iter().find(|p| p.language() == page.language()) {
+            return Err(YasgError::new(sr(
+                ErrorDuplicateTranslation,
+                &[
+                    other.relative_path().to_str().unwrap(),
+                    page.relative_path().to_str().unwrap(),
+                    page.language().unwrap_or_default(),
+                ],
+            ))
+            .kind(ErrorKind::Content));
         }
+        group.push(page);
+    }
+    for group in translations.values_mut() {
+        group.sort_by_key(|p| {
+            config
+                .languages
+                .iter()
+                .position(|l| Some(l.code.as_str()) == p.language())
+        });
+    }
+
+    // pages of a class without templates are left alone
+    let mut jobs = Vec::new();
+    for page in pages {
+        if let Some(template) = templates.for_page(page)? {
+            jobs.push((page, template));
+        }
+    }
+
+    let compiled: Vec<(&YasgFile, &YasgFile, Result<(), YasgError>)> = jobs
+        .into_par_iter()
+        .map(|(page, template)| {
+            let page_translations = &translations[page.translation_path()];
+            let result = page.compile(config, template, admonition, data, page_translations);
+            (page, template, result)
+        })
+        .collect();
+
+    // the hooks run one after the other once every page is written, not in between
+    for (page, template, result) in compiled {
+        verbose.println(&sr(
+            VerboseCompiling,
+            &[page.relative_path().to_str().unwrap()],
+        ));
+        verbose.debug(&sr(
+            VerboseUsingTemplate,
+            &[template.relative_path().to_str().unwrap()],
+        ));
+        result?;
+        report.add_page(page.relative_path(), &page.output_path());
+        config.hooks.run(
+            verbose,
+            config,
+            HookPoint::PostPage,
+            Some((page.relative_path(), &page.output_path())),
+        )?;
+    }
+
+    Ok(())
+}
+
+/************************************************************************************************/
+
+fn write_build_marker(config: &SiteConfig) -> Result<(), YasgError> {
+    // clean only removes directories carrying this marker
+    let path = config.output.join(BUILD_MARKER_FILE);
+
+    write(&path, BUILD_MARKER_CONTENTS).map_err(|e| {
+        YasgError::new(format!("{}", e))
+            .add(sr(ErrorWriteFile, &[path.to_str().unwrap()]))
+            .kind(ErrorKind::Io)
+    })
+}
+
+/************************************************************************************************/
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+/************************************************************************************************/
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    // Windows needs to know what kind of link it is, relative targets resolve from the link
+    let resolved = link
+        .parent()
+        .map_or(target.to_path_buf(), |dir| dir.join(target));
+    if resolved.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
     }
 }
 
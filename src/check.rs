@@ -0,0 +1,490 @@
+/************************************************************************************************/
+
+use crate::config::SiteConfig;
+use crate::constants::*;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::text::s;
+use crate::text::sr;
+use crate::text::Text;
+use crate::text::Text::*;
+use crate::verbose::Verbose;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs::read_to_string;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+/************************************************************************************************/
+
+struct HtmlDocument {
+    ids: HashSet<String>,
+    links: Vec<String>,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    documents: usize,
+    sources: BTreeMap<PathBuf, SourceLinks>,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct SourceLinks {
+    broken: Vec<(String, String)>,
+    external: Vec<String>,
+}
+
+/************************************************************************************************/
+
+pub(crate) fn perform_check(
+    verbose: &mut Verbose,
+    config: &SiteConfig,
+) -> Result<CheckReport, YasgError> {
+    verbose.println(s(VerboseChecking));
+    verbose.increate_indent();
+
+    if !config.output.is_dir() {
+        return Err(YasgError::new(sr(
+            ErrorOutputDirectoryNotExisting,
+            &[config.output.to_str().unwrap()],
+        ))
+        .kind(ErrorKind::Io));
+    }
+
+    verbose.println(s(VerboseBuildingFileList));
+    let mut file_list = Vec::new();
+    let mut ancestors = vec![config.output.clone()];
+    scan_output(
+        verbose,
+        &config.output,
+        &config.output,
+        &mut ancestors,
+        &mut file_list,
+    );
+    file_list.sort();
+    let files: HashSet<PathBuf> = file_list.iter().cloned().collect();
+
+    verbose.println(s(VerboseParsingHtml));
+    verbose.increate_indent();
+    let mut documents = HashMap::new();
+    for relative in file_list.iter().filter(|p| is_html(p)) {
+        verbose.println(&sr(VerboseParsing, &[relative.to_str().unwrap()]));
+
+        let mut full_path = config.output.clone();
+        full_path.push(relative);
+        let html = read_to_string(&full_path).map_err(|e| {
+            YasgError::new(format!("{}", e))
+                .add(sr(ErrorWhileReadingFile, &[relative.to_str().unwrap()]))
+                .kind(ErrorKind::Io)
+        })?;
+
+        documents.insert(relative.clone(), parse_html(&html));
+    }
+    verbose.decrease_indent();
+
+    verbose.println(s(VerboseCheckingLinks));
+    let mut report = CheckReport {
+        documents: documents.len(),
+        sources: BTreeMap::new(),
+    };
+
+    for relative in file_list.iter().filter(|p| is_html(p)) {
+        let document = &documents[relative];
+        let links = report
+            .sources
+            .entry(source_of(config, relative))
+            .or_default();
+
+        for link in &document.links {
+            if is_external(link) {
+                links.external.push(link.clone());
+            } else if let Some(reason) = check_link(relative, link, &files, &documents) {
+                links.broken.push((link.clone(), s(reason).to_string()));
+            }
+        }
+    }
+    // sources without any links are not worth reporting
+    report
+        .sources
+        .retain(|_, links| !links.broken.is_empty() || !links.external.is_empty());
+
+    verbose.decrease_indent();
+    verbose.println(s(VerboseDone));
+
+    Ok(report)
+}
+
+/************************************************************************************************/
+
+fn scan_output(
+    verbose: &mut Verbose,
+    root: &Path,
+    dir: &Path,
+    ancestors: &mut Vec<PathBuf>,
+    file_list: &mut Vec<PathBuf>,
+) {
+    if let Ok(entries) = dir.read_dir() {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            if entry.file_name().to_str().is_none() {
+                verbose.warning(&sr(WarningNameNotUtf8, &[&relative.to_string_lossy()]));
+            } else if path.is_dir() {
+                // links copied from the input may point back up the tree
+                match path.canonicalize() {
+                    Ok(canonical) if !ancestors.contains(&canonical) => {
+                        ancestors.push(canonical);
+                        scan_output(verbose, root, &path, ancestors, file_list);
+                        ancestors.pop();
+                    }
+                    _ => {}
+                }
+            } else {
+                file_list.push(relative.to_path_buf());
+            }
+        }
+    }
+}
+
+/************************************************************************************************/
+
+fn is_html(path: &Path) -> bool {
+    match path.extension() {
+        Some(extension) => extension == EXTENSION_HTML || extension == EXTENSION_HTM,
+        None => false,
+    }
+}
+
+/************************************************************************************************/
+
+impl CheckReport {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn documents(&self) -> usize {
+        self.documents
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn sources(&self) -> &BTreeMap<PathBuf, SourceLinks> {
+        // the links of every output file are reported against the file it was built from
+        &self.sources
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn broken_count(&self) -> usize {
+        self.sources.values().map(|links| links.broken.len()).sum()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn external_count(&self) -> usize {
+        self.sources
+            .values()
+            .map(|links| links.external.len())
+            .sum()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl SourceLinks {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn broken(&self) -> &[(String, String)] {
+        // the link and the reason it is broken
+        &self.broken
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn external(&self) -> &[String] {
+        &self.external
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn source_of(config: &SiteConfig, relative: &Path) -> PathBuf {
+    // 'nl/about.html' comes from 'about.nl.yasg', or from 'about.yasg' with 'lang: nl', the
+    // longest language prefix wins
+    let mut languages: Vec<_> = config.languages.iter().collect();
+    languages.sort_by_key(|language| std::cmp::Reverse(language.prefix.len()));
+
+    let mut candidates = Vec::new();
+    for language in languages {
+        if let Ok(rest) = relative.strip_prefix(&language.prefix) {
+            candidates.push((rest, Some(language.code.as_str())));
+            candidates.push((rest, None));
+        }
+    }
+    candidates.push((relative, None));
+
+    for (path, code) in candidates {
+        for extension in &[
+            EXTENSION_YASG,
+            EXTENSION_MD,
+            EXTENSION_MARKDOWN,
+            EXTENSION_HTML,
+            EXTENSION_HTM,
+        ] {
+            let mut source = path.to_path_buf();
+            match code {
+                Some(code) => source.set_extension(format!("{}.{}", code, extension)),
+                None => source.set_extension(extension),
+            };
+
+            if config.input.join(&source).is_file() {
+                return source;
+            }
+        }
+    }
+
+    relative.to_path_buf()
+}
+
+/************************************************************************************************/
+
+fn is_external(link: &str) -> bool {
+    if link.starts_with("//") {
+        return true;
+    }
+
+    // anything with a scheme like 'https:' or 'mailto:' points outside of the site
+    match link.find(':') {
+        Some(pos) => {
+            let scheme = &link[..pos];
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+        }
+        None => false,
+    }
+}
+
+/************************************************************************************************/
+
+fn check_link(
+    relative: &Path,
+    link: &str,
+    files: &HashSet<PathBuf>,
+    documents: &HashMap<PathBuf, HtmlDocument>,
+) -> Option<Text> {
+    let (path_part, fragment) = match link.find('#') {
+        Some(pos) => (&link[..pos], Some(&link[pos + 1..])),
+        None => (link, None),
+    };
+    let path_part = match path_part.find('?') {
+        Some(pos) => &path_part[..pos],
+        None => path_part,
+    };
+
+    let target = if path_part.is_empty() {
+        relative.to_path_buf()
+    } else {
+        let decoded = percent_decode(path_part);
+        let joined = if decoded.starts_with('/') {
+            PathBuf::from(decoded.trim_start_matches('/'))
+        } else {
+            let mut joined = relative.parent().map(Path::to_path_buf).unwrap_or_default();
+            joined.push(&decoded);
+            joined
+        };
+
+        let mut target = match normalize(&joined) {
+            Some(target) => target,
+            None => return Some(CheckMissingTarget),
+        };
+        if !files.contains(&target) {
+            target.push(INDEX_HTML);
+        }
+        if !files.contains(&target) {
+            return Some(CheckMissingTarget);
+        }
+        target
+    };
+
+    if let Some(fragment) = fragment {
+        if !fragment.is_empty() {
+            if let Some(document) = documents.get(&target) {
+                if !document.ids.contains(&percent_decode(fragment)) {
+                    return Some(CheckMissingFragment);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/************************************************************************************************/
+
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            // a link escaping the output directory can never resolve
+            Component::ParentDir if !normalized.pop() => return None,
+            _ => {}
+        }
+    }
+
+    Some(normalized)
+}
+
+/************************************************************************************************/
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            if let Ok(byte) = u8::from_str_radix(&value[i + 1..i + 3], 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/************************************************************************************************/
+
+fn parse_html(html: &str) -> HtmlDocument {
+    let mut document = HtmlDocument {
+        ids: HashSet::new(),
+        links: Vec::new(),
+    };
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        if rest.starts_with("!--") {
+            rest = match rest.find("-->") {
+                Some(end) => &rest[end + 3..],
+                None => "",
+            };
+            continue;
+        }
+
+        if rest.starts_with('!') || rest.starts_with('/') || rest.starts_with('?') {
+            continue;
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let tag_name = rest[..name_end].to_ascii_lowercase();
+        rest = &rest[name_end..];
+
+        let (attributes, remainder) = parse_attributes(rest);
+        rest = remainder;
+
+        for (name, value) in attributes {
+            if name == "id" || (name == "name" && tag_name == "a") {
+                document.ids.insert(value);
+            } else if (name == "href" || name == "src") && !value.is_empty() {
+                document.links.push(value);
+            }
+        }
+
+        // the contents of these elements is not markup
+        if tag_name == "script" || tag_name == "style" {
+            let closing = format!("</{}", tag_name);
+            rest = match rest.to_ascii_lowercase().find(&closing) {
+                Some(end) => &rest[end..],
+                None => "",
+            };
+        }
+    }
+
+    document
+}
+
+/************************************************************************************************/
+
+fn parse_attributes(mut rest: &str) -> (Vec<(String, String)>, &str) {
+    let mut attributes = Vec::new();
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+
+        if rest.is_empty() {
+            return (attributes, rest);
+        }
+        if let Some(remainder) = rest.strip_prefix('>') {
+            return (attributes, remainder);
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = String::new();
+        if rest.starts_with('=') {
+            rest = rest[1..].trim_start();
+
+            if rest.starts_with('"') || rest.starts_with('\'') {
+                let quote = &rest[..1];
+                rest = &rest[1..];
+                let end = rest.find(quote).unwrap_or(rest.len());
+                value = decode_entities(&rest[..end]);
+                rest = if end < rest.len() {
+                    &rest[end + 1..]
+                } else {
+                    ""
+                };
+            } else {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(rest.len());
+                value = decode_entities(&rest[..end]);
+                rest = &rest[end..];
+            }
+        }
+
+        if !name.is_empty() {
+            attributes.push((name, value));
+        }
+    }
+}
+
+/************************************************************************************************/
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/************************************************************************************************/
@@ -1,34 +1,198 @@
 /************************************************************************************************/
 
 use crate::config::SiteConfig;
+use crate::constants::*;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::hooks::HookPoint;
 use crate::text::s;
 use crate::text::sr;
 use crate::text::Text::*;
+use crate::util::glob_match;
 use crate::verbose::Verbose;
 use std::fs::remove_dir_all;
+use std::fs::remove_file;
+use std::path::Path;
+use std::path::PathBuf;
 
 /************************************************************************************************/
 
-pub fn perform_clean(verbose: &mut Verbose) {
-    verbose.println(s(VerboseCleaning));
-    verbose.increate_indent();
+#[derive(Debug, Default)]
+pub struct CleanOptions {
+    pub dry_run: bool,
+    pub keep: Vec<String>,
+}
 
-    verbose.println(s(VerboseReadingSiteConfig));
+/************************************************************************************************/
+
+pub(crate) fn perform_clean(
+    verbose: &mut Verbose,
+    config: &SiteConfig,
+    clean_options: &CleanOptions,
+) -> Result<Vec<PathBuf>, YasgError> {
+    // returns what was removed, or with a dry run what would have been removed
+    verbose.println(s(VerboseCleaning));
     verbose.increate_indent();
-    let config = SiteConfig::read_from_yaml(verbose, false, false).unwrap(); // FIXME unwrap
-    verbose.decrease_indent();
 
+    let mut removals = Vec::new();
     if config.output.exists() && config.output.is_dir() {
-        verbose.println(&sr(
-            VerboseDeletingDirectory,
-            &[&config.output.to_str().unwrap()],
-        ));
+        check_output_directory(config)?;
+
+        let everything = collect_removals(
+            &config.output,
+            Path::new(""),
+            &clean_options.keep,
+            &mut removals,
+        )?;
+
+        // the build marker stays as long as anything else stays
+        if everything {
+            removals = vec![config.output.clone()];
+        }
 
-        remove_dir_all(config.output).unwrap(); // FIXME: use unwrap_or_else to generate an error
+        if !clean_options.dry_run {
+            for path in &removals {
+                remove(verbose, path)?;
+            }
+        }
+    }
+
+    if !clean_options.dry_run {
+        config
+            .hooks
+            .run(verbose, config, HookPoint::PostClean, None)?;
     }
 
     verbose.decrease_indent();
     verbose.println(s(VerboseDone));
+
+    Ok(removals)
+}
+
+/************************************************************************************************/
+
+fn check_output_directory(config: &SiteConfig) -> Result<(), YasgError> {
+    let output = config.output.to_str().unwrap();
+
+    // the input directory and the site root must never end up inside the deleted directory
+    let input = config
+        .input
+        .canonicalize()
+        .unwrap_or_else(|_| config.input.clone());
+    if input.starts_with(&config.output) {
+        return Err(YasgError::new(sr(ErrorCleanContainsInput, &[output])).kind(ErrorKind::Config));
+    }
+
+    let config_file = config
+        .config_file
+        .canonicalize()
+        .unwrap_or_else(|_| config.config_file.clone());
+    if let Some(site_root) = config_file.parent() {
+        if site_root.starts_with(&config.output) {
+            return Err(
+                YasgError::new(sr(ErrorCleanContainsSiteRoot, &[output])).kind(ErrorKind::Config)
+            );
+        }
+    }
+
+    if !config.output.join(BUILD_MARKER_FILE).is_file() {
+        return Err(
+            YasgError::new(sr(ErrorCleanNoBuildMarker, &[output, BUILD_MARKER_FILE]))
+                .kind(ErrorKind::Config),
+        );
+    }
+
+    Ok(())
+}
+
+/************************************************************************************************/
+
+fn collect_removals(
+    dir: &Path,
+    relative: &Path,
+    keep: &[String],
+    removals: &mut Vec<PathBuf>,
+) -> Result<bool, YasgError> {
+    // returns true when nothing in the directory has to be kept
+    let mut entries: Vec<_> = dir
+        .read_dir()
+        .map_err(|e| {
+            YasgError::new(format!("{}", e))
+                .add(sr(ErrorWhileReadingFile, &[dir.to_str().unwrap()]))
+                .kind(ErrorKind::Io)
+        })?
+        .flatten()
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut everything = true;
+    let mut dir_removals = Vec::new();
+
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let entry_relative = relative.join(&*name);
+
+        if relative.as_os_str().is_empty() && name == BUILD_MARKER_FILE {
+            continue;
+        }
+
+        if is_kept(keep, &name, &entry_relative) {
+            everything = false;
+            continue;
+        }
+
+        // symbolic links are removed themselves, never followed
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        if is_dir {
+            let mut sub_removals = Vec::new();
+            if collect_removals(&path, &entry_relative, keep, &mut sub_removals)? {
+                dir_removals.push(path);
+            } else {
+                everything = false;
+                dir_removals.append(&mut sub_removals);
+            }
+        } else {
+            dir_removals.push(path);
+        }
+    }
+
+    removals.append(&mut dir_removals);
+
+    Ok(everything)
+}
+
+/************************************************************************************************/
+
+fn is_kept(keep: &[String], name: &str, relative: &Path) -> bool {
+    let relative = relative.to_string_lossy().replace('\\', "/");
+
+    keep.iter()
+        .any(|pattern| glob_match(pattern, name) || glob_match(pattern, &relative))
+}
+
+/************************************************************************************************/
+
+fn remove(verbose: &mut Verbose, path: &Path) -> Result<(), YasgError> {
+    let is_dir = path
+        .symlink_metadata()
+        .map(|m| m.file_type().is_dir())
+        .unwrap_or(false);
+
+    let result = if is_dir {
+        verbose.println(&sr(VerboseDeletingDirectory, &[path.to_str().unwrap()]));
+        remove_dir_all(path)
+    } else {
+        verbose.println(&sr(VerboseDeletingFile, &[path.to_str().unwrap()]));
+        remove_file(path)
+    };
+
+    result.map_err(|e| {
+        YasgError::new(format!("{}", e))
+            .add(sr(ErrorDeleting, &[path.to_str().unwrap()]))
+            .kind(ErrorKind::Io)
+    })
 }
 
 /************************************************************************************************/
@@ -1,26 +1,86 @@
 /************************************************************************************************/
 
 use crate::constants::*;
+use crate::engine::TemplateEngine;
+use crate::error::ErrorKind;
 use crate::error::YasgError;
-use crate::text::so;
+use crate::hooks::Hooks;
+use crate::plugin::Plugins;
+use crate::schema::Diagnostics;
+use crate::schema::SITE_SCHEMA;
 use crate::text::sr;
 use crate::text::Text::*;
+use crate::util::yaml_deep_merge;
+use crate::util::yaml_value_as_bool;
 use crate::util::yaml_value_as_string;
 use crate::verbose::Verbose;
 use std::fs::create_dir_all;
 use std::fs::File;
 use std::io::prelude::*;
+use std::path::Path;
 use std::path::PathBuf;
+use yaml_rust::Yaml;
 use yaml_rust::Yaml::Hash;
 use yaml_rust::YamlLoader;
 
 /************************************************************************************************/
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SiteConfig {
+    pub config_file: PathBuf,
     pub title: String,
     pub input: PathBuf,
     pub output: PathBuf,
+    pub data: PathBuf,
+    pub markdown: MarkdownOptions,
+    pub environment: Option<String>,
+    pub strict: bool,
+    pub languages: Vec<Language>,
+    pub exclude: Vec<String>,
+    pub include: Vec<String>,
+    pub template_engine: TemplateEngine,
+    pub symlinks: SymlinkPolicy,
+    pub hooks: Hooks,
+    pub plugins: Plugins,
+    pub values: Yaml,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Clone)]
+pub struct Language {
+    pub code: String,
+    pub title: Option<String>,
+    pub prefix: String,
+    pub strings: Yaml,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct SiteOptions {
+    pub environment: Option<String>,
+    pub site_dir: Option<PathBuf>,
+    pub config_file: Option<PathBuf>,
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct MarkdownOptions {
+    pub math: bool,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    #[default]
+    Follow,
+    Copy,
+    Skip,
 }
 
 /************************************************************************************************/
@@ -30,53 +90,113 @@ impl SiteConfig {
 
     fn new() -> SiteConfig {
         SiteConfig {
+            config_file: PathBuf::from(DEFAULT_CONFIG_FILE),
             title: String::new(),
             input: PathBuf::from(DEFAULT_INPUT_DIRECTORY),
             output: PathBuf::from(DEFAULT_OUTPUT_DIRECTORY),
+            data: PathBuf::from(DEFAULT_DATA_DIRECTORY),
+            markdown: MarkdownOptions::default(),
+            environment: None,
+            strict: false,
+            languages: Vec::new(),
+            exclude: Vec::new(),
+            include: Vec::new(),
+            template_engine: TemplateEngine::default(),
+            symlinks: SymlinkPolicy::default(),
+            hooks: Hooks::default(),
+            plugins: Plugins::default(),
+            values: Yaml::Null,
         }
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    pub fn read_from_yaml(
+    pub(crate) fn read_from_yaml(
         verbose: &mut Verbose,
-        perform_validation: bool,
-        create_output_dir: bool,
+        options: &SiteOptions,
     ) -> Result<SiteConfig, YasgError> {
         let mut sc = SiteConfig::new();
+        sc.config_file = options.config_path();
+        sc.environment = options.environment.clone();
 
-        sc.parse_yaml()?;
+        sc.parse_yaml(verbose)
+            .map_err(|e| e.kind(ErrorKind::Config))?;
 
-        sc.process_io_paths(verbose, create_output_dir);
+        sc.process_io_paths(options);
+        verbose.debug(&sr(
+            VerboseResolvedPaths,
+            &[&sc.input.to_string_lossy(), &sc.output.to_string_lossy()],
+        ));
 
-        if perform_validation {
-            match sc.validate() {
-                Ok(()) => Ok(sc),
-                Err(e) => Err(e.add(so(ErrorValidatingSiteYaml))),
-            }
-        } else {
-            Ok(sc)
+        Ok(sc)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub(crate) fn prepare_output(&mut self, verbose: &mut Verbose) -> Result<(), YasgError> {
+        // only a build needs the output directory, reading the config never creates it
+        if !self.output.exists() {
+            verbose.println(&sr(
+                VerboseCreatingOutputDirectory,
+                &[&self.output.to_string_lossy()],
+            ));
+            create_dir_all(&self.output).map_err(|e| {
+                YasgError::new(format!("{}", e))
+                    .add(sr(ErrorWriteFile, &[&self.output.to_string_lossy()]))
+                    .kind(ErrorKind::Io)
+            })?;
+        }
+        if let Ok(output) = self.output.canonicalize() {
+            self.output = output;
         }
+
+        self.validate().map_err(|e| {
+            e.add(sr(
+                ErrorValidatingSiteConfig,
+                &[self.config_file.to_str().unwrap()],
+            ))
+            .kind(ErrorKind::Config)
+        })
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn parse_yaml(&mut self) -> Result<(), YasgError> {
-        let mut f = File::open("Site.yaml").or_else(|e| {
-            Err(YasgError::new(format!("{}", e)).add(so(ErrorWhileReadingSiteYaml)))
-        })?;
-        let mut s = String::new();
-        f.read_to_string(&mut s).or_else(|e| {
-            Err(YasgError::new(format!("{}", e)).add(so(ErrorWhileReadingSiteYaml)))
-        })?;
-        let docs = YamlLoader::load_from_str(&s).or_else(|e| {
-            Err(YasgError::new(format!("{}", e)).add(so(ErrorWhileReadingSiteYaml)))
-        })?;
-        let doc = docs
-            .first()
-            .ok_or_else(|| YasgError::new(so(ErrorWhileReadingSiteYaml)))?;
+    pub(crate) fn override_paths(&mut self, input: Option<&Path>, output: Option<&Path>) {
+        // the data directory moves along with the input directory
+        if let Some(input) = input {
+            let data = self
+                .data
+                .strip_prefix(&self.input)
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|_| self.data.clone());
+            self.input = input.canonicalize().unwrap_or_else(|_| input.to_path_buf());
+            self.data = self.input.join(data);
+        }
+        if let Some(output) = output {
+            self.output = output
+                .canonicalize()
+                .unwrap_or_else(|_| output.to_path_buf());
+        }
+    }
 
-        if let Hash(h) = doc {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_yaml(&mut self, verbose: &mut Verbose) -> Result<(), YasgError> {
+        let mut diagnostics = Diagnostics::default();
+        let mut doc = SiteConfig::load_yaml_file(&self.config_file, &mut diagnostics)?;
+
+        if let Some(environment) = self.environment.clone() {
+            self.apply_environment(verbose, &mut doc, &environment, &mut diagnostics)?;
+        }
+
+        if let Hash(h) = &mut doc {
+            h.remove(&Yaml::String(YAML_ENVIRONMENTS.to_string()));
+        }
+
+        self.strict = doc[YAML_STRICT].as_bool().unwrap_or(false);
+        diagnostics.into_result(verbose, self.strict)?;
+
+        if let Hash(h) = &doc {
             for (key, value) in h {
                 if let Some(key_str) = key.as_str() {
                     if key_str == YAML_TITLE {
@@ -93,34 +213,168 @@ impl SiteConfig {
                             PathBuf::from(yaml_value_as_string(value).ok_or_else(|| {
                                 YasgError::new(sr(ErrorNoValidValueField, &[YAML_OUTPUT_PATH]))
                             })?);
+                    } else if key_str == YAML_DATA_PATH {
+                        self.data =
+                            PathBuf::from(yaml_value_as_string(value).ok_or_else(|| {
+                                YasgError::new(sr(ErrorNoValidValueField, &[YAML_DATA_PATH]))
+                            })?);
+                    } else if key_str == YAML_MARKDOWN {
+                        self.markdown.parse_yaml(value)?;
+                    } else if key_str == YAML_LANGUAGES {
+                        self.languages = Language::parse_languages(value)?;
+                    } else if key_str == YAML_TEMPLATE_ENGINE {
+                        self.template_engine = TemplateEngine::parse(
+                            &yaml_value_as_string(value).ok_or_else(|| {
+                                YasgError::new(sr(ErrorNoValidValueField, &[YAML_TEMPLATE_ENGINE]))
+                            })?,
+                        )?;
+                    } else if key_str == YAML_SYMLINKS {
+                        self.symlinks = yaml_value_as_string(value)
+                            .and_then(|s| SymlinkPolicy::from(&s))
+                            .ok_or_else(|| {
+                                YasgError::new(sr(ErrorNoValidValueField, &[YAML_SYMLINKS]))
+                            })?;
+                    } else if key_str == YAML_HOOKS {
+                        self.hooks = Hooks::parse_yaml(value)?;
+                    } else if key_str == YAML_PLUGINS {
+                        let site_dir = self.config_file.parent().unwrap_or_else(|| Path::new(""));
+                        self.plugins = Plugins::parse_yaml(value, site_dir)?;
+                    } else if key_str == YAML_EXCLUDE {
+                        self.exclude = parse_patterns(value, YAML_EXCLUDE)?;
+                    } else if key_str == YAML_INCLUDE {
+                        self.include = parse_patterns(value, YAML_INCLUDE)?;
                     }
                 } // if let Some
             } // for (key, value)
         } // if let Hash
 
+        // the default language goes first and lives at the root of the site
+        if let Some(default) = yaml_value_as_string(&doc[YAML_DEFAULT_LANGUAGE]) {
+            match self.languages.iter().position(|l| l.code == default) {
+                Some(i) => {
+                    let language = self.languages.remove(i);
+                    self.languages.insert(0, language);
+                }
+                None => return Err(YasgError::new(sr(ErrorUnknownLanguage, &[&default]))),
+            }
+        }
+        if let Some(language) = self.languages.first_mut() {
+            if doc[YAML_LANGUAGES][language.code.as_str()][YAML_PREFIX].is_badvalue() {
+                language.prefix = String::new();
+            }
+        }
+
+        self.values = doc;
+
         Ok(())
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn process_io_paths(&mut self, verbose: &mut Verbose, create_output_dir: bool) {
-        // FIXME replace unwrap with proper error handling/generating
+    fn load_yaml_file(path: &Path, diagnostics: &mut Diagnostics) -> Result<Yaml, YasgError> {
+        let file_name = path.to_str().unwrap();
+        let mut f = File::open(path).map_err(|e| {
+            YasgError::new(format!("{}", e))
+                .add(sr(ErrorWhileReadingFile, &[file_name]))
+                .kind(ErrorKind::Io)
+        })?;
+        let mut s = String::new();
+        f.read_to_string(&mut s).map_err(|e| {
+            YasgError::new(format!("{}", e))
+                .add(sr(ErrorWhileReadingFile, &[file_name]))
+                .kind(ErrorKind::Io)
+        })?;
+        let mut docs = YamlLoader::load_from_str(&s).map_err(|e| {
+            YasgError::new(format!("{}", e)).add(sr(ErrorWhileReadingFile, &[file_name]))
+        })?;
+
+        if docs.is_empty() {
+            Err(YasgError::new(sr(ErrorWhileReadingFile, &[file_name])))
+        } else {
+            let doc = docs.remove(0);
+            diagnostics.validate(file_name, &s, &doc, SITE_SCHEMA, &[]);
+            Ok(doc)
+        }
+    }
 
-        if self.input.exists() {
-            self.input = self.input.canonicalize().unwrap();
-        };
+    /*------------------------------------------------------------------------------------------*/
 
-        if self.output.exists() {
-            self.output = self.output.canonicalize().unwrap();
-        } else if create_output_dir {
+    fn apply_environment(
+        &self,
+        verbose: &mut Verbose,
+        doc: &mut Yaml,
+        environment: &str,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(), YasgError> {
+        let mut found = false;
+
+        // first the section in the config file itself, then the companion file
+        let section = doc[YAML_ENVIRONMENTS][environment].clone();
+        if !section.is_badvalue() {
             verbose.println(&sr(
-                VerboseCreatingOutputDirectory,
-                &[&self.output.to_str().unwrap()],
+                VerboseApplyingEnvironment,
+                &[environment, self.config_file.to_str().unwrap()],
+            ));
+            yaml_deep_merge(doc, &section);
+            found = true;
+        }
+
+        let companion = self.companion_file(environment);
+        if companion.is_file() {
+            verbose.println(&sr(
+                VerboseApplyingEnvironment,
+                &[environment, companion.to_str().unwrap()],
             ));
+            let overlay = SiteConfig::load_yaml_file(&companion, diagnostics)?;
+            yaml_deep_merge(doc, &overlay);
+            found = true;
+        }
+
+        if found {
+            Ok(())
+        } else {
+            Err(YasgError::new(sr(ErrorUnknownEnvironment, &[environment])))
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn companion_file(&self, environment: &str) -> PathBuf {
+        // Site.yaml becomes Site.<environment>.yaml
+        let stem = self.config_file.file_stem().unwrap().to_str().unwrap();
+        let mut file_name = format!("{}.{}", stem, environment);
+        if let Some(extension) = self.config_file.extension() {
+            file_name.push('.');
+            file_name.push_str(extension.to_str().unwrap());
+        }
 
-            create_dir_all(self.output.to_str().unwrap()).unwrap();
-            self.output = self.output.canonicalize().unwrap();
+        self.config_file.with_file_name(file_name)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn process_io_paths(&mut self, options: &SiteOptions) {
+        // paths from the command line are taken as they are, paths from the config file are
+        // relative to the directory containing that file
+        let base = self.config_file.parent().unwrap_or_else(|| Path::new(""));
+        self.input = match &options.input {
+            Some(input) => input.clone(),
+            None => base.join(&self.input),
+        };
+        self.output = match &options.output {
+            Some(output) => output.clone(),
+            None => base.join(&self.output),
+        };
+
+        if let Ok(input) = self.input.canonicalize() {
+            self.input = input;
+        }
+        if let Ok(output) = self.output.canonicalize() {
+            self.output = output;
         }
+
+        // the data directory lives inside the input directory
+        self.data = self.input.join(&self.data);
     }
 
     /*------------------------------------------------------------------------------------------*/
@@ -131,6 +385,16 @@ impl SiteConfig {
             return Err(YasgError::new(sr(ErrorNoValidValueField, &[YAML_TITLE])));
         }
 
+        // every path from here on is shown to the user and has to be valid UTF-8
+        for path in [&self.input, &self.output] {
+            if path.to_str().is_none() {
+                return Err(YasgError::new(sr(
+                    ErrorPathNotUtf8,
+                    &[&path.to_string_lossy()],
+                )));
+            }
+        }
+
         // input path needs to exist
         if !self.input.exists() {
             return Err(YasgError::new(sr(
@@ -163,8 +427,10 @@ impl SiteConfig {
             )));
         }
 
-        // output path needs to be empty
-        if self.output.read_dir().unwrap().count() > 0 {
+        // output path needs to be empty, unless a previous build left its marker behind
+        if !self.output.join(BUILD_MARKER_FILE).is_file()
+            && self.output.read_dir().unwrap().count() > 0
+        {
             return Err(YasgError::new(sr(
                 ErrorOutputIsNotEmpty,
                 &[self.output.to_str().unwrap()],
@@ -176,16 +442,163 @@ impl SiteConfig {
 
     /*------------------------------------------------------------------------------------------*/
 
-    pub fn relative_to_input(&self, path: &PathBuf) -> PathBuf {
-        // FIXME replace unwrap with proper error handling/generating
+    pub fn language(&self, code: &str) -> Option<&Language> {
+        self.languages.iter().find(|l| l.code == code)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn url(&self, output_path: &Path) -> String {
+        // root relative unless the config knows where the site is published
+        let base = yaml_value_as_string(&self.values[YAML_BASE_URL]).unwrap_or_default();
+        let path: Vec<&str> = output_path.iter().filter_map(|c| c.to_str()).collect();
+
+        format!("{}/{}", base.trim_end_matches('/'), path.join("/"))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn relative_to_input(&self, path: &Path) -> PathBuf {
+        // a path outside the input directory is kept as it is
+        path.strip_prefix(&self.input).unwrap_or(path).to_path_buf()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl SymlinkPolicy {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn from(s: &str) -> Option<SymlinkPolicy> {
+        match s {
+            SYMLINKS_FOLLOW => Some(SymlinkPolicy::Follow),
+            SYMLINKS_COPY => Some(SymlinkPolicy::Copy),
+            SYMLINKS_SKIP => Some(SymlinkPolicy::Skip),
+            _ => None,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl MarkdownOptions {
+    /*------------------------------------------------------------------------------------------*/
 
-        let prefix = self.input.to_str().unwrap();
-        let relative = path.strip_prefix(prefix).unwrap();
+    pub fn parse_yaml(&mut self, doc: &Yaml) -> Result<(), YasgError> {
+        if let Hash(h) = doc {
+            for (key, value) in h {
+                if let Some(key_str) = key.as_str() {
+                    if key_str == YAML_MATH {
+                        self.math = yaml_value_as_bool(value).ok_or_else(|| {
+                            YasgError::new(sr(ErrorNoValidValueField, &[YAML_MATH]))
+                        })?;
+                    }
+                } // if let Some
+            } // for (key, value)
+        } else {
+            return Err(YasgError::new(sr(ErrorNoValidValueField, &[YAML_MARKDOWN])));
+        }
 
-        relative.to_path_buf()
+        Ok(())
     }
 
     /*------------------------------------------------------------------------------------------*/
 }
 
 /************************************************************************************************/
+
+impl Language {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_languages(doc: &Yaml) -> Result<Vec<Language>, YasgError> {
+        let mut languages = Vec::new();
+
+        if let Hash(h) = doc {
+            for (key, value) in h {
+                if let Some(code) = yaml_value_as_string(key) {
+                    let mut language = Language {
+                        prefix: code.clone(),
+                        code,
+                        title: None,
+                        strings: Yaml::Null,
+                    };
+                    language.parse_yaml(value)?;
+                    languages.push(language);
+                }
+            } // for (key, value)
+        } else {
+            return Err(YasgError::new(sr(
+                ErrorNoValidValueField,
+                &[YAML_LANGUAGES],
+            )));
+        }
+
+        Ok(languages)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_yaml(&mut self, doc: &Yaml) -> Result<(), YasgError> {
+        if let Hash(h) = doc {
+            for (key, value) in h {
+                if let Some(key_str) = key.as_str() {
+                    if key_str == YAML_TITLE {
+                        self.title = yaml_value_as_string(value);
+                    } else if key_str == YAML_PREFIX {
+                        self.prefix = yaml_value_as_string(value)
+                            .ok_or_else(|| {
+                                YasgError::new(sr(ErrorNoValidValueField, &[YAML_PREFIX]))
+                            })?
+                            .trim_matches('/')
+                            .to_string();
+                    } else if key_str == YAML_STRINGS {
+                        self.strings = value.clone();
+                    }
+                } // if let Some
+            } // for (key, value)
+        }
+
+        Ok(())
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl SiteOptions {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn config_path(&self) -> PathBuf {
+        let site_dir = match &self.site_dir {
+            Some(site_dir) => site_dir.clone(),
+            None => PathBuf::new(),
+        };
+
+        match &self.config_file {
+            Some(config_file) => site_dir.join(config_file),
+            None => site_dir.join(DEFAULT_CONFIG_FILE),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn parse_patterns(value: &Yaml, key: &str) -> Result<Vec<String>, YasgError> {
+    let error = || YasgError::new(sr(ErrorNoValidValueField, &[key]));
+
+    value
+        .as_vec()
+        .ok_or_else(error)?
+        .iter()
+        .map(|item| yaml_value_as_string(item).ok_or_else(error))
+        .collect()
+}
+
+/************************************************************************************************/
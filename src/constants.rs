@@ -1,32 +1,191 @@
 /************************************************************************************************/
 
-pub const ARG_VERBOSE_LONG: &str = "verbose";
-pub const ARG_VERBOSE_NAME: &str = "verbose";
-pub const ARG_VERBOSE_SHORT: &str = "v";
-pub const ARG_SITE_NAME: &str = "site";
+pub const BUILD_MARKER_FILE: &str = ".yasg-build";
+pub const BUILD_MARKER_CONTENTS: &str =
+    "This directory is generated by yasg build and is removed by yasg clean.\n";
 
-pub const COMMAND_BUILD_NAME: &str = "build";
-pub const COMMAND_CLEAN_NAME: &str = "clean";
-pub const COMMAND_NEW_NAME: &str = "new";
+pub const COLOR_DEBUG: &str = "\x1b[2m";
+pub const COLOR_RESET: &str = "\x1b[0m";
+pub const COLOR_WARNING: &str = "\x1b[33m";
 
+pub const COLOR_MODE_ALWAYS: &str = "always";
+pub const COLOR_MODE_AUTO: &str = "auto";
+pub const COLOR_MODE_NEVER: &str = "never";
+
+pub const DEFAULT_CONFIG_FILE: &str = "Site.yaml";
+pub const DEFAULT_DATA_DIRECTORY: &str = "data";
 pub const DEFAULT_INPUT_DIRECTORY: &str = "contents";
 pub const DEFAULT_OUTPUT_DIRECTORY: &str = "target/site";
 
+pub const DEFAULT_ADMONITION_KINDS: &[&str] = &["note", "tip", "warning"];
+pub const DEFAULT_ADMONITION_TEMPLATE: &str = "<aside class=\"admonition {{admonition_kind}}\">\n\
+     {{#admonition_title}}<p class=\"admonition-title\">{{admonition_title}}</p>\n{{/admonition_title}}\
+     {{{admonition_body}}}</aside>\n";
+
+pub const IGNORE_FILE: &str = ".yasgignore";
+
+// dotfiles like .DS_Store, .git and .yasgignore itself are never published by default
+pub const DEFAULT_IGNORE_PATTERNS: &[&str] = &[".*"];
+
+pub const ENV_YASG_ENVIRONMENT: &str = "YASG_ENVIRONMENT";
+pub const ENV_YASG_HOOK: &str = "YASG_HOOK";
+pub const ENV_YASG_INPUT: &str = "YASG_INPUT";
+pub const ENV_YASG_OUTPUT: &str = "YASG_OUTPUT";
+pub const ENV_YASG_PAGE: &str = "YASG_PAGE";
+pub const ENV_YASG_PAGE_OUTPUT: &str = "YASG_PAGE_OUTPUT";
+
+pub const ERROR_KIND_CONFIG: &str = "config";
+pub const ERROR_KIND_CONTENT: &str = "content";
+pub const ERROR_KIND_IO: &str = "io";
+
+pub const EXIT_CODE_ERROR: i32 = 1;
+pub const EXIT_CODE_CONFIG_ERROR: i32 = 2;
+pub const EXIT_CODE_CONTENT_ERROR: i32 = 3;
+pub const EXIT_CODE_IO_ERROR: i32 = 4;
+
+pub const TEMPLATE_ENGINE_HANDLEBARS: &str = "handlebars";
+pub const TEMPLATE_ENGINE_JINJA: &str = "jinja";
+pub const TEMPLATE_ENGINE_MUSTACHE: &str = "mustache";
+
+pub const FAILURE_POLICY_FAIL: &str = "fail";
+pub const FAILURE_POLICY_IGNORE: &str = "ignore";
+pub const FAILURE_POLICY_WARN: &str = "warn";
+
+pub const SYMLINKS_COPY: &str = "copy";
+pub const SYMLINKS_FOLLOW: &str = "follow";
+pub const SYMLINKS_SKIP: &str = "skip";
+
+pub const HOOK_POLL_INTERVAL_MILLIS: u64 = 10;
+pub const HOOK_POST_BUILD: &str = "post-build";
+pub const HOOK_POST_CLEAN: &str = "post-clean";
+pub const HOOK_POST_PAGE: &str = "post-page";
+pub const HOOK_POST_SCAN: &str = "post-scan";
+pub const HOOK_PRE_BUILD: &str = "pre-build";
+
+pub const PLUGIN_DEFAULT_FUEL: u64 = 1_000_000_000;
+pub const PLUGIN_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+pub const PLUGIN_EXPORT_ALLOC: &str = "alloc";
+pub const PLUGIN_EXPORT_MEMORY: &str = "memory";
+pub const PLUGIN_TRANSFORM_MARKDOWN: &str = "transform_markdown";
+pub const PLUGIN_TRANSFORM_HTML: &str = "transform_html";
+pub const PLUGIN_TEMPLATE_DATA: &str = "template_data";
+pub const PLUGIN_GENERATE_PAGES: &str = "generate_pages";
+pub const PLUGIN_TEMPLATE_HELPERS: &str = "template_helpers";
+pub const PLUGIN_CALL_HELPER: &str = "call_helper";
+pub const PLUGIN_KEY_ARGS: &str = "args";
+pub const PLUGIN_KEY_BODY: &str = "body";
+pub const PLUGIN_KEY_FRONT_MATTER: &str = "front_matter";
+pub const PLUGIN_KEY_HASH: &str = "hash";
+pub const PLUGIN_KEY_HELPER: &str = "helper";
+pub const PLUGIN_KEY_HELPERS: &str = "helpers";
+pub const PLUGIN_KEY_HTML: &str = "html";
+pub const PLUGIN_KEY_PAGES: &str = "pages";
+pub const PLUGIN_KEY_PATH: &str = "path";
+pub const PLUGIN_KEY_RESULT: &str = "result";
+pub const PLUGIN_KEY_SOURCE: &str = "source";
+
+pub const FRONT_MATTER_TOML_DELIMITER: &str = "+++";
+pub const FRONT_MATTER_YAML_DELIMITER: &str = "---";
+
+pub const EXCERPT_SEPARATOR: &str = "<!-- more -->";
+
+pub const EXTENSION_CSV: &str = "csv";
+pub const EXTENSION_HTM: &str = "htm";
 pub const EXTENSION_HTML: &str = "html";
+pub const EXTENSION_JSON: &str = "json";
+pub const EXTENSION_MARKDOWN: &str = "markdown";
+pub const EXTENSION_MD: &str = "md";
+pub const EXTENSION_TOML: &str = "toml";
+pub const EXTENSION_YAML: &str = "yaml";
 pub const EXTENSION_YASG: &str = "yasg";
+pub const EXTENSION_YML: &str = "yml";
+
+pub const INDEX_HTML: &str = "index.html";
 
+pub const PLACEHOLDER_ADMONITION_BODY: &str = "admonition_body";
+pub const PLACEHOLDER_ADMONITION_KIND: &str = "admonition_kind";
+pub const PLACEHOLDER_ADMONITION_TITLE: &str = "admonition_title";
+pub const PLACEHOLDER_DATA: &str = "data";
+pub const PLACEHOLDER_ENVIRONMENT: &str = "environment";
 pub const PLACEHOLDER_PAGE_BODY: &str = "page_body";
 pub const PLACEHOLDER_PAGE_TITLE: &str = "page_title";
 pub const PLACEHOLDER_PAGE_DESCRIPTION: &str = "page_description";
+pub const PLACEHOLDER_PAGE_SUMMARY: &str = "page_summary";
+pub const PLACEHOLDER_PAGE_SUMMARY_TEXT: &str = "page_summary_text";
+pub const PLACEHOLDER_PAGE_WORD_COUNT: &str = "page_word_count";
+pub const PLACEHOLDER_PAGE_READING_TIME_MINUTES: &str = "page_reading_time_minutes";
+pub const PLACEHOLDER_PAGE_LANG: &str = "page_lang";
+pub const PLACEHOLDER_PAGE_URL: &str = "page_url";
+pub const PLACEHOLDER_STRINGS: &str = "strings";
+pub const PLACEHOLDER_TRANSLATIONS: &str = "translations";
+pub const PLACEHOLDER_TRANSLATION_CURRENT: &str = "current";
+pub const PLACEHOLDER_TRANSLATION_LANG: &str = "lang";
+pub const PLACEHOLDER_TRANSLATION_TITLE: &str = "title";
+pub const PLACEHOLDER_TRANSLATION_URL: &str = "url";
+pub const PLACEHOLDER_SITE: &str = "site";
+pub const PLACEHOLDER_PLUGINS: &str = "plugins";
 pub const PLACEHOLDER_SITE_TITLE: &str = "site_title";
 
+pub const LOCALE_DE: &str = "de";
+pub const LOCALE_NL: &str = "nl";
+
+pub const LOG_LEVEL_DEBUG: &str = "debug";
+pub const LOG_LEVEL_ERROR: &str = "error";
+pub const LOG_LEVEL_INFO: &str = "info";
+pub const LOG_LEVEL_WARN: &str = "warn";
+
+pub const REPORT_PHASE_CONFIG: &str = "config";
+pub const REPORT_PHASE_DATA: &str = "data";
+pub const REPORT_PHASE_FILE_LIST: &str = "file-list";
+pub const REPORT_PHASE_FILES: &str = "files";
+pub const REPORT_PHASE_TOTAL: &str = "total";
+
+pub const WORDS_PER_MINUTE: usize = 200;
+
+pub const YAML_BASE_URL: &str = "base-url";
 pub const YAML_CLASS: &str = "class";
 pub const YAML_DESCRIPTION: &str = "description";
 pub const YAML_FOR_CLASS: &str = "for-class";
+pub const YAML_TEMPLATE: &str = "template";
+pub const YAML_NAME: &str = "name";
+pub const YAML_DEFAULT: &str = "default";
 pub const YAML_TITLE: &str = "title";
 pub const YAML_INPUT_PATH: &str = "input-path";
 pub const YAML_OUTPUT_PATH: &str = "output-path";
+pub const YAML_DATA_PATH: &str = "data-path";
+pub const YAML_ENVIRONMENTS: &str = "environments";
+pub const YAML_MARKDOWN: &str = "markdown";
+pub const YAML_MATH: &str = "math";
+pub const YAML_KINDS: &str = "kinds";
+pub const YAML_PARAMS: &str = "params";
+pub const YAML_STRICT: &str = "strict";
+pub const YAML_FIELDS: &str = "fields";
+pub const YAML_LANG: &str = "lang";
+pub const YAML_LANGUAGES: &str = "languages";
+pub const YAML_DEFAULT_LANGUAGE: &str = "default-language";
+pub const YAML_PREFIX: &str = "prefix";
+pub const YAML_STRINGS: &str = "strings";
+pub const YAML_TYPE: &str = "type";
+pub const YAML_REQUIRED: &str = "required";
+pub const YAML_VALUES: &str = "values";
+pub const YAML_SYMLINKS: &str = "symlinks";
+pub const YAML_HOOKS: &str = "hooks";
+pub const YAML_COMMAND: &str = "command";
+pub const YAML_TIMEOUT: &str = "timeout";
+pub const YAML_ON_FAILURE: &str = "on-failure";
+pub const YAML_PLUGINS: &str = "plugins";
+pub const YAML_PATH: &str = "path";
+pub const YAML_FUEL: &str = "fuel";
+pub const YAML_TEMPLATE_ENGINE: &str = "template-engine";
+pub const YAML_EXCLUDE: &str = "exclude";
+pub const YAML_INCLUDE: &str = "include";
+
+pub const FIELD_TYPE_DATE: &str = "date";
+pub const FIELD_TYPE_ENUM: &str = "enum";
+pub const FIELD_TYPE_LIST: &str = "list";
+pub const FIELD_TYPE_STRING: &str = "string";
 
+pub const YASG_CLASS_ADMONITION: &str = "admonition";
 pub const YASG_CLASS_PAGE: &str = "page";
 pub const YASG_CLASS_TEMPLATE: &str = "template";
 
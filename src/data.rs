@@ -0,0 +1,233 @@
+/************************************************************************************************/
+
+use crate::build::enter_directory;
+use crate::build::entry_file_type;
+use crate::config::SiteConfig;
+use crate::config::SymlinkPolicy;
+use crate::constants::*;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::text::sr;
+use crate::text::Text::*;
+use crate::verbose::Verbose;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::path::PathBuf;
+use yaml_rust::yaml;
+use yaml_rust::Yaml;
+use yaml_rust::YamlLoader;
+
+/************************************************************************************************/
+
+pub fn load_data_files(verbose: &mut Verbose, config: &SiteConfig) -> Result<Yaml, YasgError> {
+    let mut data = yaml::Hash::new();
+
+    if config.data.is_dir() {
+        let mut ancestors = vec![config.data.canonicalize().map_err(|e| {
+            YasgError::new(format!("{}", e))
+                .add(sr(ErrorWhileReadingFile, &[&config.data.to_string_lossy()]))
+                .kind(ErrorKind::Io)
+        })?];
+        load_directory(verbose, config, &mut ancestors, &config.data, &mut data)?;
+    }
+
+    Ok(Yaml::Hash(data))
+}
+
+/************************************************************************************************/
+
+fn load_directory(
+    verbose: &mut Verbose,
+    config: &SiteConfig,
+    ancestors: &mut Vec<PathBuf>,
+    dir: &Path,
+    data: &mut yaml::Hash,
+) -> Result<(), YasgError> {
+    let read_error = |e: std::io::Error| {
+        YasgError::new(format!("{}", e))
+            .add(sr(ErrorWhileReadingFile, &[&dir.to_string_lossy()]))
+            .kind(ErrorKind::Io)
+    };
+
+    let mut entries = dir
+        .read_dir()
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(read_error)?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    // data files are read rather than copied, so a link to copy is followed as well
+    let policy = match config.symlinks {
+        SymlinkPolicy::Skip => SymlinkPolicy::Skip,
+        _ => SymlinkPolicy::Follow,
+    };
+
+    for entry in entries {
+        let path = entry.path();
+        let relative = config.relative_to_input(&path);
+        let relative = match relative.to_str() {
+            Some(relative) => relative,
+            None => {
+                verbose.warning(&sr(WarningNameNotUtf8, &[&relative.to_string_lossy()]));
+                continue;
+            }
+        };
+        let name = path.file_stem().unwrap().to_str().unwrap().to_string();
+
+        let file_type =
+            match entry_file_type(verbose, policy, &entry, relative).map_err(read_error)? {
+                Some(file_type) => file_type,
+                None => continue,
+            };
+
+        let value = if file_type.is_dir() {
+            let canonical = match enter_directory(verbose, config, &path, relative, ancestors)
+                .map_err(read_error)?
+            {
+                Some(canonical) => canonical,
+                None => continue,
+            };
+            let mut sub_data = yaml::Hash::new();
+            ancestors.push(canonical);
+            load_directory(verbose, config, ancestors, &path, &mut sub_data)?;
+            ancestors.pop();
+            Yaml::Hash(sub_data)
+        } else if !file_type.is_file() {
+            verbose.warning(&sr(WarningSpecialFile, &[relative]));
+            continue;
+        } else {
+            match parse_data_file(&path) {
+                None => {
+                    verbose.println(&sr(VerboseIgnoring, &[relative]));
+                    continue;
+                }
+                Some(result) => {
+                    verbose.println(&sr(VerboseLoadingData, &[relative]));
+                    result.map_err(|e| e.add(sr(ErrorParseErrorFor, &[relative])))?
+                }
+            }
+        };
+
+        let key = Yaml::String(name);
+        if data.contains_key(&key) {
+            return Err(YasgError::new(sr(ErrorDuplicateDataName, &[relative])));
+        }
+        data.insert(key, value);
+    }
+
+    Ok(())
+}
+
+/************************************************************************************************/
+
+fn parse_data_file(path: &Path) -> Option<Result<Yaml, YasgError>> {
+    let extension = path.extension()?.to_str()?;
+
+    let parse: fn(&str) -> Result<Yaml, YasgError> = match extension {
+        EXTENSION_YAML | EXTENSION_YML => parse_yaml,
+        EXTENSION_JSON => parse_json,
+        EXTENSION_TOML => parse_toml,
+        EXTENSION_CSV => parse_csv,
+        _ => return None,
+    };
+
+    Some(
+        read_to_string(path)
+            .map_err(|e| YasgError::new(format!("{}", e)).kind(ErrorKind::Io))
+            .and_then(|s| parse(&s).map_err(|e| e.kind(ErrorKind::Content))),
+    )
+}
+
+/************************************************************************************************/
+
+fn parse_yaml(s: &str) -> Result<Yaml, YasgError> {
+    let mut docs = YamlLoader::load_from_str(s).map_err(|e| YasgError::new(format!("{}", e)))?;
+
+    if docs.is_empty() {
+        Ok(Yaml::Null)
+    } else {
+        Ok(docs.remove(0))
+    }
+}
+
+/************************************************************************************************/
+
+fn parse_json(s: &str) -> Result<Yaml, YasgError> {
+    let value: serde_json::Value =
+        serde_json::from_str(s).map_err(|e| YasgError::new(format!("{}", e)))?;
+
+    Ok(json_to_yaml(value))
+}
+
+/************************************************************************************************/
+
+fn parse_toml(s: &str) -> Result<Yaml, YasgError> {
+    let value: toml::Value = toml::from_str(s).map_err(|e| YasgError::new(format!("{}", e)))?;
+
+    Ok(toml_to_yaml(value))
+}
+
+/************************************************************************************************/
+
+fn parse_csv(s: &str) -> Result<Yaml, YasgError> {
+    // every record becomes a hash using the header row for the keys
+    let mut reader = csv::Reader::from_reader(s.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| YasgError::new(format!("{}", e)))?
+        .clone();
+    let mut rows = Vec::new();
+
+    for record in reader.records() {
+        let record = record.map_err(|e| YasgError::new(format!("{}", e)))?;
+        let mut row = yaml::Hash::new();
+        for (header, field) in headers.iter().zip(record.iter()) {
+            row.insert(
+                Yaml::String(header.to_string()),
+                Yaml::String(field.to_string()),
+            );
+        }
+        rows.push(Yaml::Hash(row));
+    }
+
+    Ok(Yaml::Array(rows))
+}
+
+/************************************************************************************************/
+
+pub fn json_to_yaml(value: serde_json::Value) -> Yaml {
+    match value {
+        serde_json::Value::Null => Yaml::Null,
+        serde_json::Value::Bool(b) => Yaml::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        serde_json::Value::String(s) => Yaml::String(s),
+        serde_json::Value::Array(a) => Yaml::Array(a.into_iter().map(json_to_yaml).collect()),
+        serde_json::Value::Object(o) => Yaml::Hash(
+            o.into_iter()
+                .map(|(k, v)| (Yaml::String(k), json_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+/************************************************************************************************/
+
+pub fn toml_to_yaml(value: toml::Value) -> Yaml {
+    match value {
+        toml::Value::String(s) => Yaml::String(s),
+        toml::Value::Integer(i) => Yaml::Integer(i),
+        toml::Value::Float(f) => Yaml::Real(f.to_string()),
+        toml::Value::Boolean(b) => Yaml::Boolean(b),
+        toml::Value::Datetime(d) => Yaml::String(d.to_string()),
+        toml::Value::Array(a) => Yaml::Array(a.into_iter().map(toml_to_yaml).collect()),
+        toml::Value::Table(t) => Yaml::Hash(
+            t.into_iter()
+                .map(|(k, v)| (Yaml::String(k), toml_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+/************************************************************************************************/
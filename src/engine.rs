@@ -0,0 +1,255 @@
+/************************************************************************************************/
+
+use crate::constants::*;
+use crate::error::YasgError;
+use crate::plugin::Plugins;
+use crate::text::sr;
+use crate::text::Text::*;
+use handlebars::Context;
+use handlebars::Handlebars;
+use handlebars::Helper;
+use handlebars::HelperDef;
+use handlebars::RenderContext;
+use handlebars::RenderError;
+use handlebars::RenderErrorReason;
+use handlebars::ScopedJson;
+use minijinja::value::Rest;
+use minijinja::AutoEscape;
+use minijinja::Environment;
+use mustache::Data;
+use serde_json::Map;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/************************************************************************************************/
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum TemplateEngine {
+    #[default]
+    Mustache,
+    Jinja,
+    Handlebars,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug)]
+pub enum CompiledTemplate {
+    Mustache(mustache::Template, Arc<Plugins>),
+    Jinja(Environment<'static>),
+    Handlebars(Handlebars<'static>),
+}
+
+/************************************************************************************************/
+
+struct PluginHelper {
+    plugins: Arc<Plugins>,
+    name: String,
+}
+
+/************************************************************************************************/
+
+impl TemplateEngine {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn from(s: &str) -> Option<TemplateEngine> {
+        match s {
+            TEMPLATE_ENGINE_MUSTACHE => Some(TemplateEngine::Mustache),
+            TEMPLATE_ENGINE_JINJA => Some(TemplateEngine::Jinja),
+            TEMPLATE_ENGINE_HANDLEBARS => Some(TemplateEngine::Handlebars),
+            _ => None,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn parse(s: &str) -> Result<TemplateEngine, YasgError> {
+        TemplateEngine::from(s).ok_or_else(|| YasgError::new(sr(ErrorUnknownTemplateEngine, &[s])))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn compile(
+        self,
+        name: &str,
+        source: &str,
+        plugins: &Plugins,
+    ) -> Result<CompiledTemplate, YasgError> {
+        // the template helpers of the plugins are available in every engine
+        let plugins = Arc::new(plugins.clone());
+
+        match self {
+            TemplateEngine::Mustache => mustache::compile_str(source)
+                .map(|template| CompiledTemplate::Mustache(template, plugins))
+                .map_err(|e| YasgError::new(format!("{}", e))),
+            TemplateEngine::Jinja => {
+                // like Mustache and Handlebars values are escaped unless asked otherwise
+                let mut environment = Environment::new();
+                environment.set_auto_escape_callback(|_| AutoEscape::Html);
+                for helper in plugins.helper_names() {
+                    let plugins = plugins.clone();
+                    let name = helper.to_string();
+                    let function = move |args: Rest<minijinja::Value>| {
+                        call_jinja_helper(&plugins, &name, &args)
+                    };
+                    environment.add_function(helper.to_string(), function.clone());
+                    environment.add_filter(helper.to_string(), function);
+                }
+                environment
+                    .add_template_owned(name.to_string(), source.to_string())
+                    .map_err(|e| YasgError::new(format!("{}", e)))?;
+                Ok(CompiledTemplate::Jinja(environment))
+            }
+            TemplateEngine::Handlebars => {
+                let mut registry = Handlebars::new();
+                for helper in plugins.helper_names() {
+                    let plugin_helper = PluginHelper {
+                        plugins: plugins.clone(),
+                        name: helper.to_string(),
+                    };
+                    registry.register_helper(helper, Box::new(plugin_helper));
+                }
+                registry
+                    .register_template_string(name, source)
+                    .map_err(|e| YasgError::new(format!("{}", e)))?;
+                Ok(CompiledTemplate::Handlebars(registry))
+            }
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl CompiledTemplate {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn render(&self, name: &str, data: &Value) -> Result<String, YasgError> {
+        // every engine gets the same data, only the syntax of the templates differs
+        let result = match self {
+            CompiledTemplate::Mustache(template, plugins) => {
+                return render_mustache(template, plugins, data);
+            }
+            CompiledTemplate::Jinja(environment) => environment
+                .get_template(name)
+                .and_then(|template| template.render(data))
+                .map_err(|e| format!("{}", e)),
+            CompiledTemplate::Handlebars(registry) => {
+                registry.render(name, data).map_err(|e| format!("{}", e))
+            }
+        };
+
+        result.map_err(YasgError::new)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl HelperDef for PluginHelper {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        helper: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        // '{{helper}}' escapes the result and '{{{helper}}}' does not, like any other value
+        let args = helper.params().iter().map(|p| p.value().clone()).collect();
+        let hash = helper
+            .hash()
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.value().clone()))
+            .collect();
+
+        self.plugins
+            .call_helper(&self.name, args, hash)
+            .map(ScopedJson::Derived)
+            .map_err(|e| RenderErrorReason::Other(e.messages().join(" ")).into())
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn call_jinja_helper(
+    plugins: &Plugins,
+    name: &str,
+    args: &[minijinja::Value],
+) -> Result<minijinja::Value, minijinja::Error> {
+    // keyword arguments come last and end up in the hash, like with Handlebars
+    let error =
+        |message: String| minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, message);
+    let (args, hash) = match args.split_last() {
+        Some((last, rest)) if last.is_kwargs() => (rest, serde_json::to_value(last)),
+        _ => (args, Ok(Value::Object(Map::new()))),
+    };
+    let hash = match hash.map_err(|e| error(format!("{}", e)))? {
+        Value::Object(hash) => hash,
+        _ => Map::new(),
+    };
+    let args = args
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<Value>, _>>()
+        .map_err(|e| error(format!("{}", e)))?;
+
+    plugins
+        .call_helper(name, args, hash)
+        .map(minijinja::Value::from_serialize)
+        .map_err(|e| error(e.messages().join(" ")))
+}
+
+/************************************************************************************************/
+
+fn render_mustache(
+    template: &mustache::Template,
+    plugins: &Arc<Plugins>,
+    data: &Value,
+) -> Result<String, YasgError> {
+    // Mustache has no helpers, its lambdas get the unrendered text of their section and the
+    // result is rendered in its place, values of the page win over helpers with the same name
+    let mut data = mustache::to_data(data).map_err(|e| YasgError::new(format!("{}", e)))?;
+    let failure: Arc<Mutex<Option<YasgError>>> = Arc::new(Mutex::new(None));
+
+    if let Data::Map(map) = &mut data {
+        for helper in plugins.helper_names() {
+            let plugins = plugins.clone();
+            let name = helper.to_string();
+            let failure = failure.clone();
+            let lambda = move |text: String| match plugins.call_helper(
+                &name,
+                vec![Value::String(text)],
+                Map::new(),
+            ) {
+                Ok(Value::String(result)) => result,
+                Ok(Value::Null) => String::new(),
+                Ok(result) => result.to_string(),
+                Err(e) => {
+                    failure.lock().unwrap().get_or_insert(e);
+                    String::new()
+                }
+            };
+            map.entry(helper.to_string())
+                .or_insert_with(|| Data::Fun(RefCell::new(Box::new(lambda))));
+        }
+    }
+
+    let output = template
+        .render_data_to_string(&data)
+        .map_err(|e| YasgError::new(format!("{}", e)))?;
+    let failure = failure.lock().unwrap().take();
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(output),
+    }
+}
+
+/************************************************************************************************/
@@ -1,5 +1,6 @@
 /************************************************************************************************/
 
+use crate::constants::*;
 use crate::text::s;
 use crate::text::Text::*;
 
@@ -8,6 +9,16 @@ use crate::text::Text::*;
 #[derive(Debug)]
 pub struct YasgError {
     messages: Vec<String>,
+    kind: Option<ErrorKind>,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    Config,
+    Content,
+    Io,
 }
 
 /************************************************************************************************/
@@ -18,12 +29,14 @@ impl YasgError {
     pub fn new(message: String) -> YasgError {
         YasgError {
             messages: Vec::new(),
+            kind: None,
         }
         .add(message)
     }
 
     /*------------------------------------------------------------------------------------------*/
 
+    #[allow(clippy::should_implement_trait)] // adds context, not a sum
     pub fn add(mut self, message: String) -> YasgError {
         self.messages.insert(0, message);
         self
@@ -31,6 +44,39 @@ impl YasgError {
 
     /*------------------------------------------------------------------------------------------*/
 
+    pub fn kind(mut self, kind: ErrorKind) -> YasgError {
+        // the first classification wins, it is the one closest to the cause
+        if self.kind.is_none() {
+            self.kind = Some(kind);
+        }
+        self
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn error_kind(&self) -> Option<ErrorKind> {
+        self.kind
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn exit_code(&self) -> i32 {
+        match self.kind {
+            None => EXIT_CODE_ERROR,
+            Some(ErrorKind::Config) => EXIT_CODE_CONFIG_ERROR,
+            Some(ErrorKind::Content) => EXIT_CODE_CONTENT_ERROR,
+            Some(ErrorKind::Io) => EXIT_CODE_IO_ERROR,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
     pub fn show(&self) {
         eprintln!("{}", s(ErrorYasgExit));
         for msg in &self.messages {
@@ -42,3 +88,19 @@ impl YasgError {
 }
 
 /************************************************************************************************/
+
+impl ErrorKind {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Config => ERROR_KIND_CONFIG,
+            ErrorKind::Content => ERROR_KIND_CONTENT,
+            ErrorKind::Io => ERROR_KIND_IO,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
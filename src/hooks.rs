@@ -0,0 +1,286 @@
+/************************************************************************************************/
+
+use crate::config::SiteConfig;
+use crate::constants::*;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::text::sr;
+use crate::text::Text::*;
+use crate::util::yaml_value_as_string;
+use crate::verbose::Verbose;
+use std::io::stderr;
+use std::path::Path;
+use std::process::Command;
+use std::process::Stdio;
+use std::thread::sleep;
+use std::time::Duration;
+use std::time::Instant;
+use yaml_rust::Yaml;
+use yaml_rust::Yaml::Hash;
+
+/************************************************************************************************/
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HookPoint {
+    PreBuild,
+    PostScan,
+    PostPage,
+    PostBuild,
+    PostClean,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FailurePolicy {
+    Fail,
+    Warn,
+    Ignore,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Clone)]
+pub struct Hook {
+    point: HookPoint,
+    command: String,
+    timeout: Option<Duration>,
+    on_failure: FailurePolicy,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Default, Clone)]
+pub struct Hooks {
+    hooks: Vec<Hook>,
+}
+
+/************************************************************************************************/
+
+impl Hooks {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn parse_yaml(value: &Yaml) -> Result<Hooks, YasgError> {
+        // every hook point takes a command, a hook with options or a list of them
+        let mut hooks = Hooks::default();
+
+        if let Hash(h) = value {
+            for (key, value) in h {
+                if let Some(key_str) = key.as_str() {
+                    if let Some(point) = HookPoint::from(key_str) {
+                        match value {
+                            Yaml::Array(items) => {
+                                for item in items {
+                                    hooks.hooks.push(Hook::parse_yaml(point, item)?);
+                                }
+                            }
+                            _ => hooks.hooks.push(Hook::parse_yaml(point, value)?),
+                        }
+                    }
+                } // if let Some
+            } // for (key, value)
+        } // if let Hash
+
+        Ok(hooks)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn run(
+        &self,
+        verbose: &mut Verbose,
+        config: &SiteConfig,
+        point: HookPoint,
+        page: Option<(&Path, &Path)>,
+    ) -> Result<(), YasgError> {
+        for hook in self.hooks.iter().filter(|hook| hook.point == point) {
+            verbose.println(&sr(VerboseRunningHook, &[point.name(), &hook.command]));
+
+            if let Err(e) = hook.run(config, page) {
+                match hook.on_failure {
+                    FailurePolicy::Fail => return Err(e),
+                    FailurePolicy::Warn => verbose.warning(&e.messages().join(" ")),
+                    FailurePolicy::Ignore => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl Hook {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_yaml(point: HookPoint, value: &Yaml) -> Result<Hook, YasgError> {
+        let mut hook = Hook {
+            point,
+            command: String::new(),
+            timeout: None,
+            on_failure: FailurePolicy::Fail,
+        };
+
+        if let Hash(h) = value {
+            for (key, value) in h {
+                if let Some(key_str) = key.as_str() {
+                    if key_str == YAML_COMMAND {
+                        hook.command = yaml_value_as_string(value).unwrap_or_default();
+                    } else if key_str == YAML_TIMEOUT {
+                        let seconds = value.as_i64().filter(|seconds| *seconds > 0);
+                        let seconds = seconds.ok_or_else(|| {
+                            YasgError::new(sr(ErrorNoValidValueField, &[YAML_TIMEOUT]))
+                        })?;
+                        hook.timeout = Some(Duration::from_secs(seconds as u64));
+                    } else if key_str == YAML_ON_FAILURE {
+                        hook.on_failure = yaml_value_as_string(value)
+                            .and_then(|s| FailurePolicy::from(&s))
+                            .ok_or_else(|| {
+                                YasgError::new(sr(ErrorNoValidValueField, &[YAML_ON_FAILURE]))
+                            })?;
+                    }
+                } // if let Some
+            } // for (key, value)
+        } else if let Some(command) = yaml_value_as_string(value) {
+            hook.command = command;
+        }
+
+        if hook.command.is_empty() {
+            return Err(YasgError::new(sr(ErrorNoValidValueField, &[YAML_COMMAND])));
+        }
+
+        Ok(hook)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn run(&self, config: &SiteConfig, page: Option<(&Path, &Path)>) -> Result<(), YasgError> {
+        // a failing command says something is wrong with the site, like a broken page would
+        let error = |message: String| {
+            YasgError::new(message)
+                .add(sr(ErrorHookFailed, &[self.point.name(), &self.command]))
+                .kind(ErrorKind::Content)
+        };
+
+        // commands run from the site directory, like the paths in the config file
+        let mut command = shell_command(&self.command);
+        if let Some(site_dir) = config.config_file.parent() {
+            if !site_dir.as_os_str().is_empty() {
+                command.current_dir(site_dir);
+            }
+        }
+        command
+            .env(ENV_YASG_HOOK, self.point.name())
+            .env(ENV_YASG_INPUT, &config.input)
+            .env(ENV_YASG_OUTPUT, &config.output)
+            .env(
+                ENV_YASG_ENVIRONMENT,
+                config.environment.clone().unwrap_or_default(),
+            );
+        if let Some((source, output)) = page {
+            command
+                .env(ENV_YASG_PAGE, source)
+                .env(ENV_YASG_PAGE_OUTPUT, config.output.join(output));
+        }
+        // stdout is kept free for the output of yasg itself
+        command.stdin(Stdio::null()).stdout(stderr());
+
+        let mut child = command.spawn().map_err(|e| error(format!("{}", e)))?;
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(|e| error(format!("{}", e)))? {
+                break status;
+            }
+            if let Some(timeout) = self.timeout {
+                if started.elapsed() >= timeout {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    let seconds = timeout.as_secs().to_string();
+                    return Err(error(sr(ErrorHookTimeout, &[&seconds])));
+                }
+            }
+            sleep(Duration::from_millis(HOOK_POLL_INTERVAL_MILLIS));
+        };
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(error(sr(ErrorHookExitStatus, &[&status.to_string()])))
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl HookPoint {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn from(s: &str) -> Option<HookPoint> {
+        match s {
+            HOOK_PRE_BUILD => Some(HookPoint::PreBuild),
+            HOOK_POST_SCAN => Some(HookPoint::PostScan),
+            HOOK_POST_PAGE => Some(HookPoint::PostPage),
+            HOOK_POST_BUILD => Some(HookPoint::PostBuild),
+            HOOK_POST_CLEAN => Some(HookPoint::PostClean),
+            _ => None,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HookPoint::PreBuild => HOOK_PRE_BUILD,
+            HookPoint::PostScan => HOOK_POST_SCAN,
+            HookPoint::PostPage => HOOK_POST_PAGE,
+            HookPoint::PostBuild => HOOK_POST_BUILD,
+            HookPoint::PostClean => HOOK_POST_CLEAN,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl FailurePolicy {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn from(s: &str) -> Option<FailurePolicy> {
+        match s {
+            FAILURE_POLICY_FAIL => Some(FailurePolicy::Fail),
+            FAILURE_POLICY_WARN => Some(FailurePolicy::Warn),
+            FAILURE_POLICY_IGNORE => Some(FailurePolicy::Ignore),
+            _ => None,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+
+    shell
+}
+
+/************************************************************************************************/
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+
+    shell
+}
+
+/************************************************************************************************/
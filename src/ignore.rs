@@ -0,0 +1,168 @@
+/************************************************************************************************/
+
+use crate::config::SiteConfig;
+use crate::constants::*;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::text::so;
+use crate::text::sr;
+use crate::text::Text::*;
+use std::fs::read_to_string;
+use std::path::Path;
+
+/************************************************************************************************/
+
+#[derive(Debug)]
+struct IgnoreRule {
+    pattern: String,
+    negated: bool,
+    dir_only: bool,
+    anchored: bool,
+    source: String,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+/************************************************************************************************/
+
+impl IgnoreRules {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn load(config: &SiteConfig) -> Result<IgnoreRules, YasgError> {
+        // like gitignore the last matching rule wins, so the order goes from general to specific
+        let mut rules = IgnoreRules::default();
+
+        for pattern in DEFAULT_IGNORE_PATTERNS {
+            rules.add(pattern, so(IgnoreDefaultRules));
+        }
+
+        let path = config.input.join(IGNORE_FILE);
+        if path.is_file() {
+            let contents = read_to_string(&path).map_err(|e| {
+                YasgError::new(format!("{}", e))
+                    .add(sr(ErrorWhileReadingFile, &[path.to_str().unwrap()]))
+                    .kind(ErrorKind::Io)
+            })?;
+            for (i, line) in contents.lines().enumerate() {
+                rules.add(line, format!("{}:{}", IGNORE_FILE, i + 1));
+            }
+        }
+
+        let config_file = config.config_file.file_name().unwrap().to_str().unwrap();
+        for pattern in &config.exclude {
+            rules.add(pattern, format!("{} {}", config_file, YAML_EXCLUDE));
+        }
+        for pattern in &config.include {
+            // an include is a negated exclude
+            rules.add(
+                &format!("!{}", pattern),
+                format!("{} {}", config_file, YAML_INCLUDE),
+            );
+        }
+
+        Ok(rules)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn add(&mut self, line: &str, source: String) {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        // a slash anywhere but at the end ties the pattern to the input directory
+        let anchored = line.contains('/');
+        let pattern = line.trim_start_matches('/').to_string();
+
+        if !pattern.is_empty() {
+            self.rules.push(IgnoreRule {
+                pattern,
+                negated,
+                dir_only,
+                anchored,
+                source,
+            });
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn excluded_by(&self, relative: &Path, is_dir: bool) -> Option<(&str, &str)> {
+        // returns the pattern and the source of the rule that excludes the path, if any
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let name = relative.rsplit('/').next().unwrap_or(&relative);
+
+        let rule = self.rules.iter().rev().find(|rule| {
+            (is_dir || !rule.dir_only)
+                && if rule.anchored {
+                    path_match(&rule.pattern, &relative)
+                } else {
+                    path_match(&rule.pattern, name)
+                }
+        })?;
+
+        if rule.negated {
+            None
+        } else {
+            Some((&rule.pattern, &rule.source))
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn path_match(pattern: &str, path: &str) -> bool {
+    // '*' and '?' stay within a path segment, '**' crosses segments
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+
+    match_from(&pattern, &path)
+}
+
+/************************************************************************************************/
+
+fn match_from(pattern: &[char], path: &[char]) -> bool {
+    match pattern.first() {
+        None => path.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            // '**/' also matches no directories at all
+            let rest = &pattern[2..];
+            if rest.first() == Some(&'/') && match_from(&rest[1..], path) {
+                return true;
+            }
+            (0..=path.len()).any(|i| match_from(rest, &path[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=path.len() {
+                if match_from(rest, &path[i..]) {
+                    return true;
+                }
+                if i < path.len() && path[i] == '/' {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => !path.is_empty() && path[0] != '/' && match_from(&pattern[1..], &path[1..]),
+        Some(c) => path.first() == Some(c) && match_from(&pattern[1..], &path[1..]),
+    }
+}
+
+/************************************************************************************************/
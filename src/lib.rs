@@ -0,0 +1,45 @@
+/************************************************************************************************/
+
+//! YASG, Yet Another Site Generator, as a library.
+//!
+//! [`Site`] loads a site and builds it without printing anything, the result of a build is a
+//! [`BuildReport`] and checking the links of the output gives a [`CheckReport`]. [`YasgFile`] parses and renders single pages, e.g. for previews.
+
+/************************************************************************************************/
+
+mod build;
+mod check;
+mod clean;
+pub mod config;
+mod constants;
+mod data;
+pub mod engine;
+pub mod error;
+pub mod hooks;
+mod ignore;
+mod math;
+pub mod plugin;
+pub mod report;
+mod schema;
+mod site;
+mod templates;
+pub mod text;
+mod util;
+pub mod verbose;
+pub mod yasg;
+
+/************************************************************************************************/
+
+pub use crate::check::CheckReport;
+pub use crate::check::SourceLinks;
+pub use crate::clean::CleanOptions;
+pub use crate::config::SiteConfig;
+pub use crate::config::SiteOptions;
+pub use crate::error::ErrorKind;
+pub use crate::error::YasgError;
+pub use crate::report::BuildReport;
+pub use crate::site::BuildOptions;
+pub use crate::site::Site;
+pub use crate::yasg::YasgFile;
+
+/************************************************************************************************/
@@ -1,53 +1,173 @@
 /************************************************************************************************/
 
-mod build;
-mod clean;
-mod config;
-mod constants;
-mod error;
-mod new;
-mod text;
-mod util;
-mod verbose;
-mod yasg;
+#[macro_use]
+extern crate clap;
 
 /************************************************************************************************/
 
-#[macro_use]
-extern crate clap;
+mod args;
 
 /************************************************************************************************/
 
-use crate::build::perform_build;
-use crate::clean::perform_clean;
-use crate::constants::*;
-use crate::error::YasgError;
-use crate::new::perform_new;
-use crate::text::s;
-use crate::text::so;
-use crate::text::Text::*;
-use crate::verbose::Verbose;
+use crate::args::*;
 use clap::Arg;
+use clap::ArgMatches;
 use clap::SubCommand;
+use rayon::ThreadPoolBuilder;
+use std::env;
+use std::ffi::OsStr;
+use std::ffi::OsString;
 use std::io;
+use std::path::Path;
+use std::path::PathBuf;
 use std::process::exit;
+use yasg::config::SiteOptions;
+use yasg::error::ErrorKind;
+use yasg::error::YasgError;
+use yasg::report::BuildReport;
+use yasg::text::s;
+use yasg::text::set_locale;
+use yasg::text::so;
+use yasg::text::sr;
+use yasg::text::Text::*;
+use yasg::verbose::ColorMode;
+use yasg::verbose::LogLevel;
+use yasg::verbose::Verbose;
+use yasg::BuildOptions;
+use yasg::CheckReport;
+use yasg::CleanOptions;
+use yasg::Site;
 
 /************************************************************************************************/
 
 fn main() {
+    set_locale(&requested_locale());
+
     exit(match run() {
         Ok(()) => 0,
         Err(err) => {
             err.show();
-            1
+            err.exit_code()
         }
     });
 }
 
 /************************************************************************************************/
 
+fn requested_locale() -> String {
+    // the help texts need the locale before clap parses the command line
+    // arguments that are not valid unicode are reported after clap parsed them
+    let args: Vec<OsString> = env::args_os().collect();
+    let long = format!("--{}", ARG_LANG_LONG);
+    let long_with_value = format!("--{}=", ARG_LANG_LONG);
+
+    for (i, arg) in args.iter().enumerate() {
+        let arg = match arg.to_str() {
+            Some(arg) => arg,
+            None => continue,
+        };
+        if let Some(value) = arg.strip_prefix(&long_with_value) {
+            return value.to_string();
+        } else if arg == long {
+            if let Some(value) = args.get(i + 1).and_then(|value| value.to_str()) {
+                return value.to_string();
+            }
+        }
+    }
+
+    [ENV_LC_ALL, ENV_LC_MESSAGES, ENV_LANG]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+}
+
+/************************************************************************************************/
+
 fn run() -> Result<(), YasgError> {
     let mut app = app_from_crate!()
+        .arg(
+            Arg::with_name(ARG_ENV_NAME)
+                .long(ARG_ENV_LONG)
+                .takes_value(true)
+                .env(ENV_YASG_ENV)
+                .global(true)
+                .help(s(CliEnvHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_SITE_DIR_NAME)
+                .long(ARG_SITE_DIR_LONG)
+                .takes_value(true)
+                .global(true)
+                .help(s(CliSiteDirHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_CONFIG_NAME)
+                .short(ARG_CONFIG_SHORT)
+                .long(ARG_CONFIG_LONG)
+                .takes_value(true)
+                .global(true)
+                .help(s(CliConfigHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_INPUT_NAME)
+                .short(ARG_INPUT_SHORT)
+                .long(ARG_INPUT_LONG)
+                .takes_value(true)
+                .global(true)
+                .help(s(CliInputHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_OUTPUT_NAME)
+                .short(ARG_OUTPUT_SHORT)
+                .long(ARG_OUTPUT_LONG)
+                .takes_value(true)
+                .global(true)
+                .help(s(CliOutputHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_LANG_NAME)
+                .long(ARG_LANG_LONG)
+                .takes_value(true)
+                .global(true)
+                .help(s(CliLangHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_QUIET_NAME)
+                .short(ARG_QUIET_SHORT)
+                .long(ARG_QUIET_LONG)
+                .global(true)
+                .help(s(CliQuietHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_LOG_LEVEL_NAME)
+                .long(ARG_LOG_LEVEL_LONG)
+                .takes_value(true)
+                .possible_values(LogLevel::NAMES)
+                .global(true)
+                .help(s(CliLogLevelHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_TIMESTAMPS_NAME)
+                .long(ARG_TIMESTAMPS_LONG)
+                .global(true)
+                .help(s(CliTimestampsHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_TIMINGS_NAME)
+                .long(ARG_TIMINGS_LONG)
+                .global(true)
+                .help(s(CliTimingsHelp)),
+        )
+        .arg(
+            Arg::with_name(ARG_COLOR_NAME)
+                .long(ARG_COLOR_LONG)
+                .takes_value(true)
+                .possible_values(ColorMode::NAMES)
+                .default_value(ColorMode::Auto.name())
+                .global(true)
+                .help(s(CliColorHelp)),
+        )
         .subcommand(
             SubCommand::with_name(COMMAND_BUILD_NAME)
                 .about(s(CliBuildAbout))
@@ -55,6 +175,39 @@ fn run() -> Result<(), YasgError> {
                     Arg::with_name(ARG_VERBOSE_NAME)
                         .short(ARG_VERBOSE_SHORT)
                         .long(ARG_VERBOSE_LONG)
+                        .multiple(true)
+                        .help(s(CliVerboseHelp)),
+                )
+                .arg(
+                    Arg::with_name(ARG_JOBS_NAME)
+                        .short(ARG_JOBS_SHORT)
+                        .long(ARG_JOBS_LONG)
+                        .takes_value(true)
+                        .help(s(CliJobsHelp)),
+                )
+                .arg(
+                    Arg::with_name(ARG_REPORT_NAME)
+                        .long(ARG_REPORT_LONG)
+                        .takes_value(true)
+                        .help(s(CliReportHelp)),
+                )
+                .arg(
+                    Arg::with_name(ARG_FORMAT_NAME)
+                        .long(ARG_FORMAT_LONG)
+                        .takes_value(true)
+                        .possible_values(&[FORMAT_TEXT, FORMAT_JSON])
+                        .default_value(FORMAT_TEXT)
+                        .help(s(CliFormatHelp)),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(COMMAND_CHECK_NAME)
+                .about(s(CliCheckAbout))
+                .arg(
+                    Arg::with_name(ARG_VERBOSE_NAME)
+                        .short(ARG_VERBOSE_SHORT)
+                        .long(ARG_VERBOSE_LONG)
+                        .multiple(true)
                         .help(s(CliVerboseHelp)),
                 ),
         )
@@ -65,7 +218,21 @@ fn run() -> Result<(), YasgError> {
                     Arg::with_name(ARG_VERBOSE_NAME)
                         .short(ARG_VERBOSE_SHORT)
                         .long(ARG_VERBOSE_LONG)
+                        .multiple(true)
                         .help(s(CliVerboseHelp)),
+                )
+                .arg(
+                    Arg::with_name(ARG_DRY_RUN_NAME)
+                        .long(ARG_DRY_RUN_LONG)
+                        .help(s(CliDryRunHelp)),
+                )
+                .arg(
+                    Arg::with_name(ARG_KEEP_NAME)
+                        .long(ARG_KEEP_LONG)
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help(s(CliKeepHelp)),
                 ),
         )
         .subcommand(
@@ -91,17 +258,105 @@ fn run() -> Result<(), YasgError> {
             }
         }
         Some(cmd) => {
+            let json = cmd.matches.value_of(ARG_FORMAT_NAME) == Some(FORMAT_JSON);
             let mut verbose = Verbose::new();
-            if cmd.matches.is_present(ARG_VERBOSE_NAME) {
-                verbose.enable();
+            verbose.set_level(
+                match cmd
+                    .matches
+                    .value_of(ARG_LOG_LEVEL_NAME)
+                    .and_then(LogLevel::from)
+                {
+                    Some(level) => level,
+                    None if cmd.matches.is_present(ARG_QUIET_NAME) => LogLevel::Error,
+                    None => match cmd.matches.occurrences_of(ARG_VERBOSE_NAME) {
+                        0 => LogLevel::Warn,
+                        1 => LogLevel::Info,
+                        _ => LogLevel::Debug,
+                    },
+                },
+            );
+            verbose.set_timestamps(cmd.matches.is_present(ARG_TIMESTAMPS_NAME));
+            verbose.set_timings(cmd.matches.is_present(ARG_TIMINGS_NAME));
+            if let Some(mode) = cmd
+                .matches
+                .value_of(ARG_COLOR_NAME)
+                .and_then(ColorMode::from)
+            {
+                verbose.set_color(mode);
             }
 
+            let options = SiteOptions {
+                environment: text_value(&cmd.matches, ARG_ENV_NAME, ARG_ENV_LONG)?
+                    .map(String::from),
+                site_dir: text_value(&cmd.matches, ARG_SITE_DIR_NAME, ARG_SITE_DIR_LONG)?
+                    .map(PathBuf::from),
+                config_file: text_value(&cmd.matches, ARG_CONFIG_NAME, ARG_CONFIG_LONG)?
+                    .map(PathBuf::from),
+                input: text_value(&cmd.matches, ARG_INPUT_NAME, ARG_INPUT_LONG)?.map(PathBuf::from),
+                output: text_value(&cmd.matches, ARG_OUTPUT_NAME, ARG_OUTPUT_LONG)?
+                    .map(PathBuf::from),
+            };
+
             if cmd.name == COMMAND_BUILD_NAME {
-                perform_build(&mut verbose)?;
+                if let Some(jobs) = text_value(&cmd.matches, ARG_JOBS_NAME, ARG_JOBS_LONG)? {
+                    let threads = jobs.parse().ok().filter(|threads| *threads > 0);
+                    let threads = threads.ok_or_else(|| {
+                        YasgError::new(sr(ErrorInvalidArgument, &[jobs, ARG_JOBS_LONG]))
+                    })?;
+                    // the global pool can only be configured once, which is all that is needed
+                    let _ = ThreadPoolBuilder::new().num_threads(threads).build_global();
+                }
+
+                let mut report = BuildReport::new();
+                let result = Site::open(&mut verbose, &options).and_then(|site| {
+                    site.build_with(&mut verbose, &BuildOptions::new(), &mut report)
+                });
+                report.finish(verbose.warnings(), &result);
+
+                let written = match text_value(&cmd.matches, ARG_REPORT_NAME, ARG_REPORT_LONG)? {
+                    Some(path) => report.write(Path::new(path)),
+                    None => Ok(()),
+                };
+                if json {
+                    println!("{}", report.to_json());
+                }
+
+                result?;
+                written?;
+            } else if cmd.name == COMMAND_CHECK_NAME {
+                let report = Site::open(&mut verbose, &options)?.check(&mut verbose)?;
+                print_check_report(&report);
+
+                let broken = report.broken_count();
+                if broken > 0 {
+                    return Err(
+                        YasgError::new(sr(ErrorBrokenLinksFound, &[&broken.to_string()]))
+                            .kind(ErrorKind::Content),
+                    );
+                }
             } else if cmd.name == COMMAND_CLEAN_NAME {
-                perform_clean(&mut verbose);
+                let clean_options = CleanOptions {
+                    dry_run: cmd.matches.is_present(ARG_DRY_RUN_NAME),
+                    keep: match cmd.matches.values_of_os(ARG_KEEP_NAME) {
+                        Some(values) => values
+                            .map(|value| text(value, ARG_KEEP_LONG).map(String::from))
+                            .collect::<Result<_, _>>()?,
+                        None => Vec::new(),
+                    },
+                };
+                let removals =
+                    Site::open(&mut verbose, &options)?.clean(&mut verbose, &clean_options)?;
+                if clean_options.dry_run {
+                    for path in removals {
+                        println!("{}", sr(CleanWouldRemove, &[&path.to_string_lossy()]));
+                    }
+                }
             } else if cmd.name == COMMAND_NEW_NAME {
-                perform_new(&mut verbose);
+                // TODO implement 'new' command
+                return Err(YasgError::new(sr(
+                    ErrorCommandNotImplemented,
+                    &[COMMAND_NEW_NAME],
+                )));
             }
 
             Ok(())
@@ -110,3 +365,51 @@ fn run() -> Result<(), YasgError> {
 }
 
 /************************************************************************************************/
+
+fn print_check_report(report: &CheckReport) {
+    for (source, links) in report.sources() {
+        println!("{}", source.to_string_lossy());
+        for (link, reason) in links.broken() {
+            println!("  {}", sr(CheckBrokenLink, &[link, reason]));
+        }
+        for link in links.external() {
+            println!("  {}", sr(CheckExternalLink, &[link]));
+        }
+    }
+
+    println!(
+        "{}",
+        sr(
+            CheckSummary,
+            &[
+                &report.documents().to_string(),
+                &report.broken_count().to_string(),
+                &report.external_count().to_string()
+            ]
+        )
+    );
+}
+
+/************************************************************************************************/
+
+fn text_value<'a>(
+    matches: &'a ArgMatches,
+    name: &str,
+    long: &str,
+) -> Result<Option<&'a str>, YasgError> {
+    matches
+        .value_of_os(name)
+        .map(|value| text(value, long))
+        .transpose()
+}
+
+/************************************************************************************************/
+
+fn text<'a>(value: &'a OsStr, long: &str) -> Result<&'a str, YasgError> {
+    // clap panics on values that are not valid unicode, the paths included
+    value
+        .to_str()
+        .ok_or_else(|| YasgError::new(sr(ErrorInvalidArgument, &[&value.to_string_lossy(), long])))
+}
+
+/************************************************************************************************/
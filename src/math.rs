@@ -0,0 +1,686 @@
+/************************************************************************************************/
+
+use crate::error::YasgError;
+use crate::text::sr;
+use crate::text::Text::*;
+use pulldown_cmark::Event;
+use pulldown_cmark::Parser;
+use pulldown_cmark::Tag;
+
+/************************************************************************************************/
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Superscript,
+    Subscript,
+    Number(String),
+    Char(char),
+    Command(String),
+    Text(String, String),
+}
+
+/************************************************************************************************/
+
+struct MathParser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+    display: bool,
+}
+
+/************************************************************************************************/
+
+pub fn render_math_in_markdown(markdown: &str) -> Result<String, YasgError> {
+    let mut output = String::new();
+    let mut position = 0;
+
+    // code blocks, fenced or indented, are copied verbatim, everything else is scanned for math
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        if let Event::Start(Tag::CodeBlock(_)) = event {
+            output.push_str(&render_math_in_text(&markdown[position..range.start])?);
+            output.push_str(&markdown[range.start..range.end]);
+            position = range.end;
+        }
+    }
+
+    output.push_str(&render_math_in_text(&markdown[position..])?);
+
+    Ok(output)
+}
+
+/************************************************************************************************/
+
+fn render_math_in_text(text: &str) -> Result<String, YasgError> {
+    let mut output = String::new();
+    let mut i = 0;
+
+    while let Some(c) = text[i..].chars().next() {
+        if c == '\\' {
+            // escaped characters, including '\$', are left to the Markdown parser
+            let escaped = text[i + 1..].chars().next().map_or(0, char::len_utf8);
+            output.push_str(&text[i..i + 1 + escaped]);
+            i += 1 + escaped;
+        } else if c == '`' {
+            // code spans are never scanned for math
+            let run = text[i..].chars().take_while(|c| *c == '`').count();
+            let delimiter = "`".repeat(run);
+            match find_code_span_end(&text[i + run..], &delimiter) {
+                Some(end) => {
+                    let span_end = i + run + end + run;
+                    output.push_str(&text[i..span_end]);
+                    i = span_end;
+                }
+                None => {
+                    output.push_str(&delimiter);
+                    i += run;
+                }
+            }
+        } else if text[i..].starts_with("$$") {
+            let start = i + 2;
+            let end = text[start..].find("$$").ok_or_else(|| {
+                YasgError::new(sr(ErrorMathUnterminated, &[first_line(&text[i..])]))
+            })?;
+            output.push_str(&tex_to_mathml(&text[start..start + end], true)?);
+            i = start + end + 2;
+        } else if c == '$' {
+            match find_inline_math_end(&text[i + 1..]) {
+                Some(end) => {
+                    output.push_str(&tex_to_mathml(&text[i + 1..i + 1 + end], false)?);
+                    i += end + 2;
+                }
+                None => {
+                    output.push('$');
+                    i += 1;
+                }
+            }
+        } else {
+            output.push(c);
+            i += c.len_utf8();
+        }
+    }
+
+    Ok(output)
+}
+
+/************************************************************************************************/
+
+fn find_code_span_end(text: &str, delimiter: &str) -> Option<usize> {
+    let mut offset = 0;
+
+    while let Some(pos) = text[offset..].find(delimiter) {
+        let start = offset + pos;
+        let run = text[start..].chars().take_while(|c| *c == '`').count();
+        if run == delimiter.len() {
+            return Some(start);
+        }
+        offset = start + run;
+    }
+
+    None
+}
+
+/************************************************************************************************/
+
+fn find_inline_math_end(text: &str) -> Option<usize> {
+    // like pandoc: '$' must be followed by a non-space and the closing '$' must be preceded by a
+    // non-space and not followed by a digit, and like TeX the first unescaped '$' ends the math,
+    // so neither '$' in 'Cost $5 and $10' opens math and amounts stay plain text
+    let paragraph = match text.find("\n\n") {
+        Some(end) => &text[..end],
+        None => text,
+    };
+
+    if paragraph.chars().next().is_none_or(char::is_whitespace) {
+        return None;
+    }
+
+    let mut previous = ' ';
+    for (pos, c) in paragraph.char_indices() {
+        if c == '`' {
+            // math never continues into a code span
+            return None;
+        } else if c == '$' && previous != '\\' {
+            let next = paragraph[pos + 1..].chars().next();
+            if previous.is_whitespace() || next.is_some_and(|n| n.is_ascii_digit()) {
+                return None;
+            }
+            return Some(pos);
+        }
+        previous = c;
+    }
+
+    None
+}
+
+/************************************************************************************************/
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or("")
+}
+
+/************************************************************************************************/
+
+pub fn tex_to_mathml(tex: &str, display: bool) -> Result<String, YasgError> {
+    let mut parser = MathParser {
+        source: tex,
+        tokens: tokenize(tex)?,
+        position: 0,
+        display,
+    };
+
+    let row = parser.parse_row()?;
+    if parser.position < parser.tokens.len() {
+        return Err(parser.error_unbalanced());
+    }
+
+    if display {
+        Ok(format!(
+            "<math display=\"block\"><mrow>{}</mrow></math>",
+            row
+        ))
+    } else {
+        Ok(format!("<math><mrow>{}</mrow></math>", row))
+    }
+}
+
+/************************************************************************************************/
+
+fn tokenize(tex: &str) -> Result<Vec<Token>, YasgError> {
+    let chars: Vec<char> = tex.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '{' {
+            tokens.push(Token::Open);
+            i += 1;
+        } else if c == '}' {
+            tokens.push(Token::Close);
+            i += 1;
+        } else if c == '^' {
+            tokens.push(Token::Superscript);
+            i += 1;
+        } else if c == '_' {
+            tokens.push(Token::Subscript);
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && i + 1 < chars.len() && chars[i + 1].is_ascii_digit())
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c == '\\' {
+            i += 1;
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            if i == start && i < chars.len() {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+
+            if TEXT_COMMANDS.contains(&name.as_str()) {
+                // the argument of text commands is taken literally, including its spaces
+                while i < chars.len() && chars[i].is_whitespace() {
+                    i += 1;
+                }
+                if i >= chars.len() || chars[i] != '{' {
+                    return Err(YasgError::new(sr(
+                        ErrorMathMissingArgument,
+                        &[&format!("\\{}", name), tex],
+                    )));
+                }
+                let mut depth = 0;
+                let start = i + 1;
+                loop {
+                    if i >= chars.len() {
+                        return Err(YasgError::new(sr(ErrorMathUnbalancedBraces, &[tex])));
+                    }
+                    if chars[i] == '{' {
+                        depth += 1;
+                    } else if chars[i] == '}' {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    i += 1;
+                }
+                tokens.push(Token::Text(name, chars[start..i].iter().collect()));
+                i += 1;
+            } else {
+                tokens.push(Token::Command(name));
+            }
+        } else {
+            tokens.push(Token::Char(c));
+            i += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+/************************************************************************************************/
+
+impl<'a> MathParser<'a> {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        if token.is_some() {
+            self.position += 1;
+        }
+
+        token
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn error_unbalanced(&self) -> YasgError {
+        YasgError::new(sr(ErrorMathUnbalancedBraces, &[self.source]))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn error_missing_argument(&self, name: &str) -> YasgError {
+        YasgError::new(sr(ErrorMathMissingArgument, &[name, self.source]))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_row(&mut self) -> Result<String, YasgError> {
+        let mut row = String::new();
+
+        while let Some(token) = self.peek() {
+            if *token == Token::Close || *token == Token::Command(String::from("right")) {
+                break;
+            }
+
+            let (base, movable_limits) = match token {
+                Token::Superscript | Token::Subscript => (String::from("<mrow></mrow>"), false),
+                _ => self.parse_atom()?,
+            };
+            row.push_str(&self.parse_scripts(base, movable_limits)?);
+        }
+
+        Ok(row)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_scripts(&mut self, base: String, movable_limits: bool) -> Result<String, YasgError> {
+        let mut subscript = None;
+        let mut superscript = None;
+
+        loop {
+            match self.peek() {
+                Some(Token::Subscript) if subscript.is_none() => {
+                    self.next();
+                    subscript = Some(self.parse_argument("_")?);
+                }
+                Some(Token::Superscript) if superscript.is_none() => {
+                    self.next();
+                    superscript = Some(self.parse_argument("^")?);
+                }
+                _ => break,
+            }
+        }
+
+        let (under, over, both) = if movable_limits && self.display {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+
+        Ok(match (subscript, superscript) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<{0}>{1}{2}</{0}>", under, base, sub),
+            (None, Some(sup)) => format!("<{0}>{1}{2}</{0}>", over, base, sup),
+            (Some(sub), Some(sup)) => format!("<{0}>{1}{2}{3}</{0}>", both, base, sub, sup),
+        })
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_argument(&mut self, name: &str) -> Result<String, YasgError> {
+        match self.peek() {
+            None | Some(Token::Close) | Some(Token::Superscript) | Some(Token::Subscript) => {
+                Err(self.error_missing_argument(name))
+            }
+            _ => Ok(self.parse_atom()?.0),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_atom(&mut self) -> Result<(String, bool), YasgError> {
+        match self.next() {
+            Some(Token::Open) => {
+                let row = self.parse_row()?;
+                match self.next() {
+                    Some(Token::Close) => Ok((format!("<mrow>{}</mrow>", row), false)),
+                    _ => Err(self.error_unbalanced()),
+                }
+            }
+            Some(Token::Number(number)) => Ok((format!("<mn>{}</mn>", number), false)),
+            Some(Token::Char(c)) => Ok((char_to_mathml(c), false)),
+            Some(Token::Text(name, text)) => Ok((text_to_mathml(&name, &text), false)),
+            Some(Token::Command(name)) => self.parse_command(&name),
+            _ => Err(self.error_unbalanced()),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_command(&mut self, name: &str) -> Result<(String, bool), YasgError> {
+        if let Some(symbol) = lookup(IDENTIFIERS, name) {
+            return Ok((format!("<mi>{}</mi>", symbol), false));
+        }
+        if let Some(symbol) = lookup(OPERATORS, name) {
+            return Ok((format!("<mo>{}</mo>", symbol), false));
+        }
+        if let Some(symbol) = lookup(LARGE_OPERATORS, name) {
+            return Ok((format!("<mo>{}</mo>", symbol), true));
+        }
+        if FUNCTIONS.contains(&name) {
+            return Ok((format!("<mi>{}</mi>", name), name == "lim"));
+        }
+        if let Some(width) = lookup(SPACES, name) {
+            return Ok((format!("<mspace width=\"{}\"/>", width), false));
+        }
+
+        let command = format!("\\{}", name);
+        match name {
+            "frac" => {
+                let numerator = self.parse_argument(&command)?;
+                let denominator = self.parse_argument(&command)?;
+                Ok((
+                    format!("<mfrac>{}{}</mfrac>", numerator, denominator),
+                    false,
+                ))
+            }
+            "sqrt" => {
+                if self.peek() == Some(&Token::Char('[')) {
+                    self.next();
+                    let mut index = String::new();
+                    loop {
+                        match self.peek() {
+                            Some(Token::Char(']')) => break,
+                            None => return Err(self.error_unbalanced()),
+                            _ => index.push_str(&self.parse_atom()?.0),
+                        }
+                    }
+                    self.next();
+                    let radicand = self.parse_argument(&command)?;
+                    Ok((
+                        format!("<mroot>{}<mrow>{}</mrow></mroot>", radicand, index),
+                        false,
+                    ))
+                } else {
+                    let radicand = self.parse_argument(&command)?;
+                    Ok((format!("<msqrt>{}</msqrt>", radicand), false))
+                }
+            }
+            "left" => {
+                let open = self.parse_delimiter(&command)?;
+                let row = self.parse_row()?;
+                if self.next() != Some(Token::Command(String::from("right"))) {
+                    return Err(YasgError::new(sr(
+                        ErrorMathUnbalancedDelimiters,
+                        &[self.source],
+                    )));
+                }
+                let close = self.parse_delimiter("\\right")?;
+                Ok((format!("<mrow>{}{}{}</mrow>", open, row, close), false))
+            }
+            "right" => Err(YasgError::new(sr(
+                ErrorMathUnbalancedDelimiters,
+                &[self.source],
+            ))),
+            "{" | "}" | "|" => Ok((format!("<mo>{}</mo>", escape(name)), false)),
+            "$" | "%" | "#" | "&" | "_" => Ok((format!("<mi>{}</mi>", escape(name)), false)),
+            _ => Err(YasgError::new(sr(
+                ErrorMathUnknownCommand,
+                &[&command, self.source],
+            ))),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn parse_delimiter(&mut self, name: &str) -> Result<String, YasgError> {
+        match self.next() {
+            Some(Token::Char('.')) => Ok(String::new()),
+            Some(Token::Char(c)) => Ok(format!("<mo fence=\"true\">{}</mo>", escape_char(c))),
+            Some(Token::Command(ref command))
+                if command == "{" || command == "}" || command == "|" =>
+            {
+                Ok(format!("<mo fence=\"true\">{}</mo>", escape(command)))
+            }
+            Some(Token::Command(ref command)) if lookup(DELIMITERS, command).is_some() => {
+                Ok(format!(
+                    "<mo fence=\"true\">{}</mo>",
+                    lookup(DELIMITERS, command).unwrap()
+                ))
+            }
+            _ => Err(self.error_missing_argument(name)),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn char_to_mathml(c: char) -> String {
+    if c.is_alphabetic() {
+        format!("<mi>{}</mi>", escape_char(c))
+    } else if c == '\'' {
+        String::from("<mo>&#x2032;</mo>")
+    } else {
+        format!("<mo>{}</mo>", escape_char(c))
+    }
+}
+
+/************************************************************************************************/
+
+fn text_to_mathml(name: &str, text: &str) -> String {
+    if name == "text" || name == "mbox" {
+        format!("<mtext>{}</mtext>", escape(text))
+    } else {
+        format!("<mi mathvariant=\"normal\">{}</mi>", escape(text))
+    }
+}
+
+/************************************************************************************************/
+
+fn escape(text: &str) -> String {
+    text.chars().map(escape_char).collect()
+}
+
+/************************************************************************************************/
+
+fn escape_char(c: char) -> String {
+    // the generated markup is embedded in Markdown, so characters that are significant to the
+    // Markdown parser are written as character references as well
+    match c {
+        '&' => String::from("&amp;"),
+        '<' => String::from("&lt;"),
+        '>' => String::from("&gt;"),
+        '"' => String::from("&quot;"),
+        '*' | '_' | '`' | '[' | ']' | '\\' | '~' | '|' | '!' => format!("&#{};", c as u32),
+        _ => c.to_string(),
+    }
+}
+
+/************************************************************************************************/
+
+fn lookup(table: &[(&str, &'static str)], name: &str) -> Option<&'static str> {
+    table.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+}
+
+/************************************************************************************************/
+
+const TEXT_COMMANDS: &[&str] = &["text", "mbox", "mathrm", "operatorname"];
+
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "min", "max", "sup", "inf", "det", "dim", "gcd", "lim",
+];
+
+const IDENTIFIERS: &[(&str, &str)] = &[
+    ("alpha", "&#x3B1;"),
+    ("beta", "&#x3B2;"),
+    ("gamma", "&#x3B3;"),
+    ("delta", "&#x3B4;"),
+    ("epsilon", "&#x3F5;"),
+    ("varepsilon", "&#x3B5;"),
+    ("zeta", "&#x3B6;"),
+    ("eta", "&#x3B7;"),
+    ("theta", "&#x3B8;"),
+    ("vartheta", "&#x3D1;"),
+    ("iota", "&#x3B9;"),
+    ("kappa", "&#x3BA;"),
+    ("lambda", "&#x3BB;"),
+    ("mu", "&#x3BC;"),
+    ("nu", "&#x3BD;"),
+    ("xi", "&#x3BE;"),
+    ("pi", "&#x3C0;"),
+    ("rho", "&#x3C1;"),
+    ("sigma", "&#x3C3;"),
+    ("tau", "&#x3C4;"),
+    ("upsilon", "&#x3C5;"),
+    ("phi", "&#x3D5;"),
+    ("varphi", "&#x3C6;"),
+    ("chi", "&#x3C7;"),
+    ("psi", "&#x3C8;"),
+    ("omega", "&#x3C9;"),
+    ("Gamma", "&#x393;"),
+    ("Delta", "&#x394;"),
+    ("Theta", "&#x398;"),
+    ("Lambda", "&#x39B;"),
+    ("Xi", "&#x39E;"),
+    ("Pi", "&#x3A0;"),
+    ("Sigma", "&#x3A3;"),
+    ("Upsilon", "&#x3A5;"),
+    ("Phi", "&#x3A6;"),
+    ("Psi", "&#x3A8;"),
+    ("Omega", "&#x3A9;"),
+    ("infty", "&#x221E;"),
+    ("partial", "&#x2202;"),
+    ("nabla", "&#x2207;"),
+    ("emptyset", "&#x2205;"),
+    ("hbar", "&#x210F;"),
+    ("ell", "&#x2113;"),
+];
+
+const OPERATORS: &[(&str, &str)] = &[
+    ("times", "&#xD7;"),
+    ("cdot", "&#x22C5;"),
+    ("pm", "&#xB1;"),
+    ("mp", "&#x2213;"),
+    ("div", "&#xF7;"),
+    ("ast", "&#x2217;"),
+    ("star", "&#x22C6;"),
+    ("circ", "&#x2218;"),
+    ("leq", "&#x2264;"),
+    ("le", "&#x2264;"),
+    ("geq", "&#x2265;"),
+    ("ge", "&#x2265;"),
+    ("neq", "&#x2260;"),
+    ("ne", "&#x2260;"),
+    ("approx", "&#x2248;"),
+    ("equiv", "&#x2261;"),
+    ("sim", "&#x223C;"),
+    ("propto", "&#x221D;"),
+    ("ll", "&#x226A;"),
+    ("gg", "&#x226B;"),
+    ("forall", "&#x2200;"),
+    ("exists", "&#x2203;"),
+    ("in", "&#x2208;"),
+    ("notin", "&#x2209;"),
+    ("subset", "&#x2282;"),
+    ("subseteq", "&#x2286;"),
+    ("supset", "&#x2283;"),
+    ("supseteq", "&#x2287;"),
+    ("cup", "&#x222A;"),
+    ("cap", "&#x2229;"),
+    ("setminus", "&#x2216;"),
+    ("wedge", "&#x2227;"),
+    ("land", "&#x2227;"),
+    ("vee", "&#x2228;"),
+    ("lor", "&#x2228;"),
+    ("neg", "&#xAC;"),
+    ("lnot", "&#xAC;"),
+    ("to", "&#x2192;"),
+    ("rightarrow", "&#x2192;"),
+    ("leftarrow", "&#x2190;"),
+    ("leftrightarrow", "&#x2194;"),
+    ("Rightarrow", "&#x21D2;"),
+    ("Leftarrow", "&#x21D0;"),
+    ("Leftrightarrow", "&#x21D4;"),
+    ("implies", "&#x21D2;"),
+    ("iff", "&#x21D4;"),
+    ("mapsto", "&#x21A6;"),
+    ("ldots", "&#x2026;"),
+    ("dots", "&#x2026;"),
+    ("cdots", "&#x22EF;"),
+    ("vdots", "&#x22EE;"),
+    ("ddots", "&#x22F1;"),
+    ("langle", "&#x27E8;"),
+    ("rangle", "&#x27E9;"),
+    ("lfloor", "&#x230A;"),
+    ("rfloor", "&#x230B;"),
+    ("lceil", "&#x2308;"),
+    ("rceil", "&#x2309;"),
+    ("mid", "&#x2223;"),
+    ("prime", "&#x2032;"),
+    ("int", "&#x222B;"),
+    ("iint", "&#x222C;"),
+    ("oint", "&#x222E;"),
+];
+
+const LARGE_OPERATORS: &[(&str, &str)] = &[
+    ("sum", "&#x2211;"),
+    ("prod", "&#x220F;"),
+    ("coprod", "&#x2210;"),
+    ("bigcup", "&#x22C3;"),
+    ("bigcap", "&#x22C2;"),
+];
+
+const DELIMITERS: &[(&str, &str)] = &[
+    ("langle", "&#x27E8;"),
+    ("rangle", "&#x27E9;"),
+    ("lfloor", "&#x230A;"),
+    ("rfloor", "&#x230B;"),
+    ("lceil", "&#x2308;"),
+    ("rceil", "&#x2309;"),
+    ("vert", "&#124;"),
+    ("Vert", "&#x2016;"),
+];
+
+const SPACES: &[(&str, &str)] = &[
+    (",", "0.1667em"),
+    (":", "0.2222em"),
+    (";", "0.2778em"),
+    (" ", "0.3333em"),
+    ("quad", "1em"),
+    ("qquad", "2em"),
+];
+
+/************************************************************************************************/
@@ -1,12 +0,0 @@
-/************************************************************************************************/
-
-use crate::verbose::Verbose;
-
-/************************************************************************************************/
-
-pub fn perform_new(_verbose: &mut Verbose) {
-    // TODO implement 'new' command
-    unimplemented!();
-}
-
-/************************************************************************************************/
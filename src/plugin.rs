@@ -0,0 +1,415 @@
+/************************************************************************************************/
+
+use crate::constants::*;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::text::sr;
+use crate::text::Text::*;
+use crate::util::is_relative_inside;
+use crate::util::yaml_value_as_string;
+use crate::verbose::Verbose;
+use crate::yasg::YasgFormat;
+use serde_json::json;
+use serde_json::Map;
+use serde_json::Value;
+use std::fs::read;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use wasmi::Config;
+use wasmi::Engine;
+use wasmi::Linker;
+use wasmi::Module;
+use wasmi::Store;
+use wasmi::StoreLimits;
+use wasmi::StoreLimitsBuilder;
+use yaml_rust::Yaml;
+use yaml_rust::Yaml::Hash;
+
+/************************************************************************************************/
+
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    name: String,
+    path: PathBuf,
+    fuel: u64,
+    module: Option<Arc<Module>>,
+    helpers: Vec<String>,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Default, Clone)]
+pub struct Plugins {
+    engine: Option<Engine>,
+    plugins: Vec<Plugin>,
+}
+
+/************************************************************************************************/
+
+impl Plugins {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn parse_yaml(value: &Yaml, site_dir: &Path) -> Result<Plugins, YasgError> {
+        // a list of paths to modules or plugins with options, relative to the site directory
+        let mut plugins = Plugins::default();
+
+        if let Yaml::Array(items) = value {
+            for item in items {
+                let mut path = yaml_value_as_string(item);
+                let mut fuel = PLUGIN_DEFAULT_FUEL;
+
+                if let Hash(h) = item {
+                    for (key, value) in h {
+                        if let Some(key_str) = key.as_str() {
+                            if key_str == YAML_PATH {
+                                path = yaml_value_as_string(value);
+                            } else if key_str == YAML_FUEL {
+                                fuel = value.as_i64().filter(|fuel| *fuel > 0).ok_or_else(|| {
+                                    YasgError::new(sr(ErrorNoValidValueField, &[YAML_FUEL]))
+                                })? as u64;
+                            }
+                        } // if let Some
+                    } // for (key, value)
+                } // if let Hash
+
+                let path =
+                    path.ok_or_else(|| YasgError::new(sr(ErrorNoValidValueField, &[YAML_PATH])))?;
+                let path = site_dir.join(path);
+                let name = path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                plugins.plugins.push(Plugin {
+                    name,
+                    path,
+                    fuel,
+                    module: None,
+                    helpers: Vec::new(),
+                });
+            }
+        }
+
+        Ok(plugins)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn load(&mut self, verbose: &mut Verbose) -> Result<(), YasgError> {
+        // loading twice changes nothing
+        if self.plugins.is_empty() || self.engine.is_some() {
+            return Ok(());
+        }
+
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+
+        for plugin in self.plugins.iter_mut() {
+            let path = plugin.path.to_str().unwrap();
+            verbose.println(&sr(VerboseLoadingPlugin, &[path]));
+
+            let wasm = read(&plugin.path).map_err(|e| {
+                YasgError::new(format!("{}", e))
+                    .add(sr(ErrorLoadingPlugin, &[path]))
+                    .kind(ErrorKind::Io)
+            })?;
+            let module = Module::new(&engine, &wasm[..]).map_err(|e| {
+                YasgError::new(format!("{}", e))
+                    .add(sr(ErrorLoadingPlugin, &[path]))
+                    .kind(ErrorKind::Config)
+            })?;
+            // nothing is granted to a plugin, so there is nothing it could import
+            if let Some(import) = module.imports().next() {
+                let import = format!("{}.{}", import.module(), import.name());
+                return Err(YasgError::new(sr(ErrorPluginImport, &[&import]))
+                    .add(sr(ErrorLoadingPlugin, &[path]))
+                    .kind(ErrorKind::Config));
+            }
+            plugin.module = Some(Arc::new(module));
+        }
+        self.engine = Some(engine);
+
+        // the helpers are asked for once, the templates are compiled with them
+        for i in 0..self.plugins.len() {
+            let helpers = self.list_helpers(&self.plugins[i])?;
+            for helper in helpers.iter() {
+                if let Some(other) = self.plugins[..i]
+                    .iter()
+                    .find(|other| other.helpers.contains(helper))
+                {
+                    return Err(YasgError::new(sr(
+                        ErrorDuplicateHelper,
+                        &[&other.name, &self.plugins[i].name, helper],
+                    ))
+                    .kind(ErrorKind::Config));
+                }
+            }
+            self.plugins[i].helpers = helpers;
+        }
+
+        Ok(())
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn list_helpers(&self, plugin: &Plugin) -> Result<Vec<String>, YasgError> {
+        let output = match self.call(plugin, PLUGIN_TEMPLATE_HELPERS, &json!({}))? {
+            Some(output) => output,
+            None => return Ok(Vec::new()),
+        };
+        let names: Option<Vec<String>> = output
+            .get(PLUGIN_KEY_HELPERS)
+            .and_then(Value::as_array)
+            .and_then(|names| {
+                names
+                    .iter()
+                    .map(|name| {
+                        name.as_str()
+                            .filter(|name| !name.is_empty())
+                            .map(String::from)
+                    })
+                    .collect()
+            });
+
+        // helpers that cannot be called are a mistake in the plugin, not in the templates
+        match names {
+            Some(names) if names.is_empty() || exports(plugin, PLUGIN_CALL_HELPER) => Ok(names),
+            _ => Err(invalid_output(plugin, PLUGIN_TEMPLATE_HELPERS)),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn helper_names(&self) -> impl Iterator<Item = &str> {
+        self.plugins
+            .iter()
+            .flat_map(|plugin| plugin.helpers.iter().map(String::as_str))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn call_helper(
+        &self,
+        helper: &str,
+        args: Vec<Value>,
+        hash: Map<String, Value>,
+    ) -> Result<Value, YasgError> {
+        // called by the template engines while rendering, nothing means an empty result
+        let plugin = match self
+            .plugins
+            .iter()
+            .find(|plugin| plugin.helpers.iter().any(|name| name == helper))
+        {
+            Some(plugin) => plugin,
+            None => return Ok(Value::Null),
+        };
+        let input = json!({
+            PLUGIN_KEY_HELPER: helper,
+            PLUGIN_KEY_ARGS: args,
+            PLUGIN_KEY_HASH: hash,
+        });
+
+        match self.call(plugin, PLUGIN_CALL_HELPER, &input)? {
+            Some(output) => output
+                .get(PLUGIN_KEY_RESULT)
+                .cloned()
+                .ok_or_else(|| invalid_output(plugin, PLUGIN_CALL_HELPER)),
+            None => Ok(Value::Null),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn transform_markdown(&self, page: &Value, body: String) -> Result<String, YasgError> {
+        self.transform(PLUGIN_TRANSFORM_MARKDOWN, PLUGIN_KEY_BODY, page, body)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn transform_html(&self, page: &Value, html: String) -> Result<String, YasgError> {
+        self.transform(PLUGIN_TRANSFORM_HTML, PLUGIN_KEY_HTML, page, html)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn transform(
+        &self,
+        export: &str,
+        key: &str,
+        page: &Value,
+        mut content: String,
+    ) -> Result<String, YasgError> {
+        // every plugin works on the result of the one before it
+        for plugin in self.plugins.iter() {
+            let mut input = page.clone();
+            input[key] = Value::String(content.clone());
+
+            if let Some(output) = self.call(plugin, export, &input)? {
+                match output.get(key) {
+                    Some(Value::String(s)) => content = s.clone(),
+                    Some(_) => return Err(invalid_output(plugin, export)),
+                    None => {}
+                }
+            }
+        }
+
+        Ok(content)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn template_data(&self, page: &Value) -> Result<Option<Value>, YasgError> {
+        // the templates find the data of a plugin under 'plugins.<name>'
+        let mut data = Map::new();
+
+        for plugin in self.plugins.iter() {
+            if let Some(output) = self.call(plugin, PLUGIN_TEMPLATE_DATA, page)? {
+                data.insert(plugin.name.clone(), output);
+            }
+        }
+
+        if data.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(Value::Object(data)))
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn generate_pages(
+        &self,
+        pages: Vec<Value>,
+    ) -> Result<Vec<(String, PathBuf, String)>, YasgError> {
+        // returns the plugin name, the path relative to the input directory and the source
+        let input = json!({ PLUGIN_KEY_PAGES: pages });
+        let mut generated = Vec::new();
+
+        for plugin in self.plugins.iter() {
+            if let Some(output) = self.call(plugin, PLUGIN_GENERATE_PAGES, &input)? {
+                let pages = output
+                    .get(PLUGIN_KEY_PAGES)
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| invalid_output(plugin, PLUGIN_GENERATE_PAGES))?;
+                for page in pages {
+                    let path = page.get(PLUGIN_KEY_PATH).and_then(Value::as_str);
+                    let source = page.get(PLUGIN_KEY_SOURCE).and_then(Value::as_str);
+                    match (path, source) {
+                        // generated pages stay inside the input directory
+                        (Some(path), Some(source)) if is_page_path(Path::new(path)) => {
+                            generated.push((
+                                plugin.name.clone(),
+                                PathBuf::from(path),
+                                source.to_string(),
+                            ));
+                        }
+                        _ => return Err(invalid_output(plugin, PLUGIN_GENERATE_PAGES)),
+                    }
+                }
+            }
+        }
+
+        Ok(generated)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn call(
+        &self,
+        plugin: &Plugin,
+        export: &str,
+        input: &Value,
+    ) -> Result<Option<Value>, YasgError> {
+        let (engine, module) = match (&self.engine, &plugin.module) {
+            (Some(engine), Some(module)) => (engine, module),
+            _ => return Ok(None),
+        };
+        if !exports(plugin, export) {
+            return Ok(None);
+        }
+
+        let error = |e: &dyn std::fmt::Display| {
+            YasgError::new(format!("{}", e))
+                .add(sr(ErrorPlugin, &[&plugin.name, export]))
+                .kind(ErrorKind::Content)
+        };
+
+        // a fresh instance for every call, without any imports there is no way out of the sandbox
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(PLUGIN_MEMORY_LIMIT)
+            .instances(1)
+            .build();
+        let mut store = Store::new(engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store.set_fuel(plugin.fuel).map_err(|e| error(&e))?;
+        let linker = Linker::<StoreLimits>::new(engine);
+        let instance = linker
+            .instantiate(&mut store, module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| error(&e))?;
+
+        let memory = instance
+            .get_memory(&store, PLUGIN_EXPORT_MEMORY)
+            .ok_or_else(|| invalid_output(plugin, export))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, PLUGIN_EXPORT_ALLOC)
+            .map_err(|e| error(&e))?;
+        let function = instance
+            .get_typed_func::<(i32, i32), i64>(&store, export)
+            .map_err(|e| error(&e))?;
+
+        let input = input.to_string();
+        let length = input.len() as i32;
+        let pointer = alloc.call(&mut store, length).map_err(|e| error(&e))?;
+        memory
+            .write(&mut store, pointer as u32 as usize, input.as_bytes())
+            .map_err(|e| error(&e))?;
+
+        // the result points to JSON in the memory of the plugin, 0 means nothing changed
+        let result = function
+            .call(&mut store, (pointer, length))
+            .map_err(|e| error(&e))?;
+        if result == 0 {
+            return Ok(None);
+        }
+        let pointer = (result as u64 >> 32) as usize;
+        let length = (result as u64 & 0xffff_ffff) as usize;
+        // the length comes from the plugin, so it is checked before anything is read
+        let output = pointer
+            .checked_add(length)
+            .and_then(|end| memory.data(&store).get(pointer..end))
+            .ok_or_else(|| invalid_output(plugin, export))?;
+
+        serde_json::from_slice(output)
+            .map(Some)
+            .map_err(|_| invalid_output(plugin, export))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn invalid_output(plugin: &Plugin, export: &str) -> YasgError {
+    YasgError::new(sr(ErrorPluginOutput, &[&plugin.name, export])).kind(ErrorKind::Content)
+}
+
+/************************************************************************************************/
+
+fn exports(plugin: &Plugin, export: &str) -> bool {
+    plugin
+        .module
+        .as_ref()
+        .is_some_and(|module| module.exports().any(|e| e.name() == export))
+}
+
+/************************************************************************************************/
+
+fn is_page_path(path: &Path) -> bool {
+    // a file name below the input directory with the extension of one of the page formats
+    is_relative_inside(path) && YasgFormat::from_path(path).is_some()
+}
+
+/************************************************************************************************/
@@ -0,0 +1,222 @@
+/************************************************************************************************/
+
+use crate::config::SiteConfig;
+use crate::constants::*;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::text::sr;
+use crate::text::Text::*;
+use serde_json::json;
+use serde_json::Value;
+use std::fs::write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+
+/************************************************************************************************/
+
+pub struct BuildReport {
+    started: Instant,
+    config: Value,
+    pages: Vec<(PathBuf, PathBuf)>,
+    assets: Vec<(PathBuf, PathBuf)>,
+    warnings: Vec<String>,
+    error_kind: Option<ErrorKind>,
+    errors: Vec<String>,
+    exit_code: i32,
+    timings: Vec<(&'static str, Duration)>,
+}
+
+/************************************************************************************************/
+
+impl Default for BuildReport {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn default() -> BuildReport {
+        BuildReport::new()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl BuildReport {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn new() -> BuildReport {
+        BuildReport {
+            started: Instant::now(),
+            config: Value::Null,
+            pages: Vec::new(),
+            assets: Vec::new(),
+            warnings: Vec::new(),
+            error_kind: None,
+            errors: Vec::new(),
+            exit_code: 0,
+            timings: Vec::new(),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn set_config(&mut self, config: &SiteConfig) {
+        self.config = json!({
+            "file": config.config_file,
+            "input": config.input,
+            "output": config.output,
+            "data": config.data,
+            "environment": config.environment,
+            "strict": config.strict,
+        });
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn add_page(&mut self, source: &Path, output: &Path) {
+        self.pages
+            .push((source.to_path_buf(), output.to_path_buf()));
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn add_asset(&mut self, source: &Path, output: &Path) {
+        self.assets
+            .push((source.to_path_buf(), output.to_path_buf()));
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn add_timing(&mut self, phase: &'static str, started: Instant) {
+        self.timings.push((phase, started.elapsed()));
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn finish(&mut self, warnings: &[String], result: &Result<(), YasgError>) {
+        self.warnings = warnings.to_vec();
+        if let Err(e) = result {
+            self.error_kind = e.error_kind();
+            self.errors = e.messages().to_vec();
+            self.exit_code = e.exit_code();
+        }
+
+        let started = self.started;
+        self.add_timing(REPORT_PHASE_TOTAL, started);
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn pages(&self) -> &[(PathBuf, PathBuf)] {
+        &self.pages
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn assets(&self) -> &[(PathBuf, PathBuf)] {
+        &self.assets
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn error_kind(&self) -> Option<ErrorKind> {
+        self.error_kind
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn timings(&self) -> &[(&'static str, Duration)] {
+        &self.timings
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn to_json(&self) -> String {
+        let files = |files: &[(PathBuf, PathBuf)]| -> Vec<Value> {
+            files
+                .iter()
+                .map(|(source, output)| json!({ "source": source, "output": output }))
+                .collect()
+        };
+
+        let kind = self.error_kind.map(ErrorKind::name);
+        let errors: Vec<Value> = self
+            .errors
+            .iter()
+            .map(|message| match split_location(message) {
+                Some((file, line, column)) => json!({
+                    "kind": kind,
+                    "message": message,
+                    "file": file,
+                    "line": line,
+                    "column": column,
+                }),
+                None => json!({ "kind": kind, "message": message }),
+            })
+            .collect();
+
+        let mut timings = serde_json::Map::new();
+        for (phase, duration) in &self.timings {
+            timings.insert(phase.to_string(), json!(duration.as_millis() as u64));
+        }
+
+        let report = json!({
+            "success": self.errors.is_empty(),
+            "exit-code": self.exit_code,
+            "config": self.config,
+            "pages": files(&self.pages),
+            "assets": files(&self.assets),
+            "warnings": self.warnings,
+            "errors": errors,
+            "timings-ms": timings,
+        });
+
+        serde_json::to_string_pretty(&report).unwrap() // a Value always serializes
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn write(&self, path: &Path) -> Result<(), YasgError> {
+        write(path, self.to_json()).map_err(|e| {
+            YasgError::new(format!("{}", e))
+                .add(sr(ErrorWriteFile, &[path.to_str().unwrap()]))
+                .kind(ErrorKind::Io)
+        })
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn split_location(message: &str) -> Option<(&str, u64, u64)> {
+    // diagnostics start with 'file:line:column: '
+    let mut parts = message.splitn(4, ':');
+    let file = parts.next()?;
+    let line = parts.next()?.parse().ok()?;
+    let column = parts.next()?.parse().ok()?;
+    parts.next()?;
+
+    Some((file, line, column))
+}
+
+/************************************************************************************************/
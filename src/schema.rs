@@ -0,0 +1,629 @@
+/************************************************************************************************/
+
+use crate::constants::*;
+use crate::error::YasgError;
+use crate::text::s;
+use crate::text::so;
+use crate::text::sr;
+use crate::text::Text::*;
+use crate::util::edit_distance;
+use crate::util::yaml_value_as_bool;
+use crate::util::yaml_value_as_string;
+use crate::util::yaml_value_as_string_list;
+use crate::verbose::Verbose;
+use std::collections::HashMap;
+use yaml_rust::parser::Event;
+use yaml_rust::parser::MarkedEventReceiver;
+use yaml_rust::parser::Parser;
+use yaml_rust::scanner::Marker;
+use yaml_rust::Yaml;
+use yaml_rust::Yaml::Hash;
+
+/************************************************************************************************/
+
+#[derive(Clone, Copy)]
+pub enum ValueType {
+    Scalar,
+    Boolean,
+    List,
+    Map(&'static [Field]),
+    MapOf(&'static [Field]),
+    Environments,
+    Any,
+}
+
+/************************************************************************************************/
+
+pub struct Field {
+    pub name: &'static str,
+    pub value_type: ValueType,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug)]
+pub struct FieldDeclaration {
+    pub name: String,
+    pub field_type: FieldType,
+    pub required: bool,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug)]
+pub enum FieldType {
+    String,
+    Date,
+    List,
+    Enum(Vec<String>),
+}
+
+/************************************************************************************************/
+
+#[derive(Default)]
+pub struct Diagnostics {
+    warnings: Vec<String>,
+    errors: Vec<String>,
+}
+
+/************************************************************************************************/
+
+struct Validation<'a> {
+    file_name: &'a str,
+    declarations: &'a [FieldDeclaration],
+    positions: HashMap<Vec<String>, Marker>,
+    path: Vec<String>,
+}
+
+/************************************************************************************************/
+
+#[derive(Default)]
+struct KeyPositions {
+    stack: Vec<Frame>,
+    path: Vec<String>,
+    positions: HashMap<Vec<String>, Marker>,
+}
+
+/************************************************************************************************/
+
+enum Frame {
+    Map { expect_key: bool },
+    Sequence { index: usize },
+}
+
+/************************************************************************************************/
+
+pub static SITE_SCHEMA: &[Field] = &[
+    Field {
+        name: YAML_TITLE,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_BASE_URL,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_INPUT_PATH,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_OUTPUT_PATH,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_DATA_PATH,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_STRICT,
+        value_type: ValueType::Boolean,
+    },
+    Field {
+        name: YAML_MARKDOWN,
+        value_type: ValueType::Map(MARKDOWN_SCHEMA),
+    },
+    Field {
+        name: YAML_PARAMS,
+        value_type: ValueType::Any,
+    },
+    Field {
+        name: YAML_TEMPLATE_ENGINE,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_SYMLINKS,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_HOOKS,
+        value_type: ValueType::Map(HOOKS_SCHEMA),
+    },
+    Field {
+        name: YAML_PLUGINS,
+        value_type: ValueType::List,
+    },
+    Field {
+        name: YAML_EXCLUDE,
+        value_type: ValueType::List,
+    },
+    Field {
+        name: YAML_INCLUDE,
+        value_type: ValueType::List,
+    },
+    Field {
+        name: YAML_LANGUAGES,
+        value_type: ValueType::MapOf(LANGUAGE_SCHEMA),
+    },
+    Field {
+        name: YAML_DEFAULT_LANGUAGE,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_ENVIRONMENTS,
+        value_type: ValueType::Environments,
+    },
+];
+
+pub static FRONT_MATTER_SCHEMA: &[Field] = &[
+    Field {
+        name: YAML_CLASS,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_FOR_CLASS,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_TEMPLATE,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_NAME,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_DEFAULT,
+        value_type: ValueType::Boolean,
+    },
+    Field {
+        name: YAML_TITLE,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_DESCRIPTION,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_MARKDOWN,
+        value_type: ValueType::Map(MARKDOWN_SCHEMA),
+    },
+    Field {
+        name: YAML_KINDS,
+        value_type: ValueType::List,
+    },
+    Field {
+        name: YAML_FIELDS,
+        value_type: ValueType::Any,
+    },
+    Field {
+        name: YAML_LANG,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_TEMPLATE_ENGINE,
+        value_type: ValueType::Scalar,
+    },
+];
+
+static HOOKS_SCHEMA: &[Field] = &[
+    Field {
+        name: HOOK_PRE_BUILD,
+        value_type: ValueType::Any,
+    },
+    Field {
+        name: HOOK_POST_SCAN,
+        value_type: ValueType::Any,
+    },
+    Field {
+        name: HOOK_POST_PAGE,
+        value_type: ValueType::Any,
+    },
+    Field {
+        name: HOOK_POST_BUILD,
+        value_type: ValueType::Any,
+    },
+    Field {
+        name: HOOK_POST_CLEAN,
+        value_type: ValueType::Any,
+    },
+];
+
+static LANGUAGE_SCHEMA: &[Field] = &[
+    Field {
+        name: YAML_TITLE,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_PREFIX,
+        value_type: ValueType::Scalar,
+    },
+    Field {
+        name: YAML_STRINGS,
+        value_type: ValueType::Any,
+    },
+];
+
+static MARKDOWN_SCHEMA: &[Field] = &[Field {
+    name: YAML_MATH,
+    value_type: ValueType::Boolean,
+}];
+
+/************************************************************************************************/
+
+impl Diagnostics {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn validate(
+        &mut self,
+        file_name: &str,
+        source: &str,
+        doc: &Yaml,
+        schema: &'static [Field],
+        declarations: &[FieldDeclaration],
+    ) {
+        let mut key_positions = KeyPositions::default();
+        // syntax errors are reported by the YamlLoader, here only the positions matter
+        let _ = Parser::new(source.chars()).load(&mut key_positions, false);
+
+        let mut validation = Validation {
+            file_name,
+            declarations,
+            positions: key_positions.positions,
+            path: Vec::new(),
+        };
+
+        if let Yaml::Hash(_) = doc {
+            validation.check_type(self, ValueType::Map(schema), doc);
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn into_result(self, verbose: &mut Verbose, strict: bool) -> Result<(), YasgError> {
+        // in strict mode every warning is an error as well, otherwise they are shown even when
+        // there are errors
+        let mut errors = self.errors;
+        if strict {
+            errors.extend(self.warnings);
+        } else {
+            for warning in self.warnings.iter() {
+                verbose.warning(warning);
+            }
+        }
+
+        match errors
+            .into_iter()
+            .rev()
+            .fold(None, |e: Option<YasgError>, msg| match e {
+                None => Some(YasgError::new(msg)),
+                Some(e) => Some(e.add(msg)),
+            }) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl<'a> Validation<'a> {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn location(&self) -> (String, String) {
+        match self.positions.get(&self.path) {
+            Some(marker) => (marker.line().to_string(), (marker.col() + 1).to_string()),
+            None => (String::from("?"), String::from("?")),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn check_type(&mut self, diagnostics: &mut Diagnostics, value_type: ValueType, value: &Yaml) {
+        let expected = match (value_type, value) {
+            (ValueType::Any, _) => return,
+            (ValueType::Scalar, Yaml::String(_))
+            | (ValueType::Scalar, Yaml::Integer(_))
+            | (ValueType::Scalar, Yaml::Real(_))
+            | (ValueType::Scalar, Yaml::Boolean(_))
+            | (ValueType::Boolean, Yaml::Boolean(_))
+            | (ValueType::List, Yaml::Array(_)) => return,
+            (ValueType::Map(fields), Yaml::Hash(h)) => {
+                // declared fields only exist at the top level of the front matter
+                let top_level = self.path.is_empty();
+                for (key, value) in h {
+                    if let Some(key_str) = key.as_str() {
+                        self.path.push(key_str.to_string());
+                        let declaration = self.declarations.iter().find(|d| d.name == key_str);
+                        match fields.iter().find(|f| f.name == key_str) {
+                            Some(field) => self.check_type(diagnostics, field.value_type, value),
+                            None => match declaration {
+                                Some(d) if top_level => self.check_field(diagnostics, d, value),
+                                _ => self.unknown_key(diagnostics, key_str, fields, top_level),
+                            },
+                        }
+                        self.path.pop();
+                    }
+                }
+                if top_level {
+                    for declaration in self.declarations.iter().filter(|d| d.required) {
+                        if !h.contains_key(&Yaml::String(declaration.name.clone())) {
+                            diagnostics.errors.push(sr(
+                                ErrorMissingRequiredField,
+                                &[self.file_name, &declaration.name],
+                            ));
+                        }
+                    }
+                }
+                return;
+            }
+            (ValueType::MapOf(fields), Yaml::Hash(h)) => {
+                for (key, value) in h {
+                    if let Some(key_str) = key.as_str() {
+                        self.path.push(key_str.to_string());
+                        self.check_type(diagnostics, ValueType::Map(fields), value);
+                        self.path.pop();
+                    }
+                }
+                return;
+            }
+            (ValueType::Environments, Yaml::Hash(h)) => {
+                // every environment may override any of the site settings
+                for (key, value) in h {
+                    if let Some(key_str) = key.as_str() {
+                        self.path.push(key_str.to_string());
+                        self.check_type(diagnostics, ValueType::Map(SITE_SCHEMA), value);
+                        self.path.pop();
+                    }
+                }
+                return;
+            }
+            (ValueType::Scalar, _) => s(TypeScalar),
+            (ValueType::Boolean, _) => s(TypeBoolean),
+            (ValueType::List, _) => s(TypeList),
+            (ValueType::Map(_), _) | (ValueType::MapOf(_), _) | (ValueType::Environments, _) => {
+                s(TypeMap)
+            }
+        };
+
+        let (line, column) = self.location();
+        diagnostics.errors.push(sr(
+            ErrorWrongValueType,
+            &[
+                self.file_name,
+                &line,
+                &column,
+                &self.path.join("."),
+                expected,
+            ],
+        ));
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn check_field(
+        &mut self,
+        diagnostics: &mut Diagnostics,
+        declaration: &FieldDeclaration,
+        value: &Yaml,
+    ) {
+        let expected = match (&declaration.field_type, value) {
+            (FieldType::String, Yaml::String(_))
+            | (FieldType::String, Yaml::Integer(_))
+            | (FieldType::String, Yaml::Real(_))
+            | (FieldType::List, Yaml::Array(_)) => return,
+            (FieldType::Date, Yaml::String(date)) if is_date(date) => return,
+            (FieldType::Enum(values), _)
+                if yaml_value_as_string(value).is_some_and(|v| values.contains(&v)) =>
+            {
+                return
+            }
+            (FieldType::String, _) => so(TypeScalar),
+            (FieldType::Date, _) => so(TypeDate),
+            (FieldType::List, _) => so(TypeList),
+            (FieldType::Enum(values), _) => sr(TypeEnum, &[&values.join(", ")]),
+        };
+
+        let (line, column) = self.location();
+        diagnostics.errors.push(sr(
+            ErrorWrongValueType,
+            &[
+                self.file_name,
+                &line,
+                &column,
+                &self.path.join("."),
+                &expected,
+            ],
+        ));
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn unknown_key(
+        &mut self,
+        diagnostics: &mut Diagnostics,
+        key: &str,
+        fields: &[Field],
+        top_level: bool,
+    ) {
+        let (line, column) = self.location();
+        let declared = self.declarations.iter().filter(|_| top_level);
+        let suggestion = fields
+            .iter()
+            .map(|f| f.name)
+            .chain(declared.map(|d| d.name.as_str()))
+            .map(|name| (edit_distance(key, name), name))
+            .filter(|(distance, name)| *distance <= 2.max(name.len() / 3))
+            .min();
+
+        diagnostics.warnings.push(match suggestion {
+            Some((_, name)) => sr(
+                WarningUnknownKeySuggestion,
+                &[self.file_name, &line, &column, key, name],
+            ),
+            None => sr(WarningUnknownKey, &[self.file_name, &line, &column, key]),
+        });
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl FieldDeclaration {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn parse_yaml(doc: &Yaml) -> Result<Vec<FieldDeclaration>, YasgError> {
+        let mut declarations = Vec::new();
+
+        if let Hash(h) = doc {
+            for (key, value) in h {
+                if let Some(key_str) = key.as_str() {
+                    let invalid = || YasgError::new(sr(ErrorInvalidFieldDeclaration, &[key_str]));
+                    let mut field_type = None;
+                    let mut values = None;
+                    let mut required = false;
+
+                    if let Hash(fh) = value {
+                        for (key, value) in fh {
+                            if let Some(key_str) = key.as_str() {
+                                if key_str == YAML_TYPE {
+                                    field_type = yaml_value_as_string(value);
+                                } else if key_str == YAML_REQUIRED {
+                                    required = yaml_value_as_bool(value).ok_or_else(invalid)?;
+                                } else if key_str == YAML_VALUES {
+                                    values = yaml_value_as_string_list(value);
+                                }
+                            } // if let Some
+                        } // for (key, value)
+                    } // if let Hash
+
+                    let field_type = match (field_type.as_deref(), values) {
+                        (Some(FIELD_TYPE_STRING), _) => FieldType::String,
+                        (Some(FIELD_TYPE_DATE), _) => FieldType::Date,
+                        (Some(FIELD_TYPE_LIST), _) => FieldType::List,
+                        (Some(FIELD_TYPE_ENUM), Some(values)) => FieldType::Enum(values),
+                        _ => return Err(invalid()),
+                    };
+
+                    declarations.push(FieldDeclaration {
+                        name: key_str.to_string(),
+                        field_type,
+                        required,
+                    });
+                } // if let Some
+            } // for (key, value)
+        } else {
+            return Err(YasgError::new(sr(ErrorNoValidValueField, &[YAML_FIELDS])));
+        }
+
+        Ok(declarations)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl KeyPositions {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn begin_value(&mut self) {
+        if let Some(Frame::Sequence { index }) = self.stack.last() {
+            self.path.push(index.to_string());
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn end_value(&mut self) {
+        match self.stack.last_mut() {
+            Some(Frame::Map { expect_key }) if !*expect_key => {
+                self.path.pop();
+                *expect_key = true;
+            }
+            Some(Frame::Sequence { index }) => {
+                self.path.pop();
+                *index += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn is_date(s: &str) -> bool {
+    // YYYY-MM-DD, the format YAML uses for dates
+    let parts: Vec<&str> = s.split('-').collect();
+    if parts.len() != 3
+        || parts[0].len() != 4
+        || parts[1].len() != 2
+        || parts[2].len() != 2
+        || !parts.iter().all(|p| p.chars().all(|c| c.is_ascii_digit()))
+    {
+        return false;
+    }
+
+    let month: u32 = parts[1].parse().unwrap_or(0);
+    let day: u32 = parts[2].parse().unwrap_or(0);
+
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+/************************************************************************************************/
+
+impl MarkedEventReceiver for KeyPositions {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        match event {
+            Event::MappingStart(_) => {
+                self.begin_value();
+                self.stack.push(Frame::Map { expect_key: true });
+            }
+            Event::SequenceStart(_) => {
+                self.begin_value();
+                self.stack.push(Frame::Sequence { index: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.end_value();
+            }
+            Event::Scalar(value, ..) => {
+                if let Some(Frame::Map { expect_key }) = self.stack.last_mut() {
+                    if *expect_key {
+                        *expect_key = false;
+                        self.path.push(value);
+                        self.positions.insert(self.path.clone(), marker);
+                        return;
+                    }
+                }
+                self.begin_value();
+                self.end_value();
+            }
+            Event::Alias(_) => {
+                self.begin_value();
+                self.end_value();
+            }
+            _ => {}
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
@@ -0,0 +1,213 @@
+/************************************************************************************************/
+
+use crate::build::build_file_list;
+use crate::build::perform_build;
+use crate::check::perform_check;
+use crate::check::CheckReport;
+use crate::clean::perform_clean;
+use crate::clean::CleanOptions;
+use crate::config::SiteConfig;
+use crate::config::SiteOptions;
+use crate::data::load_data_files;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::report::BuildReport;
+use crate::templates::Templates;
+use crate::text::sr;
+use crate::text::Text::*;
+use crate::util::is_relative_inside;
+use crate::verbose::LogLevel;
+use crate::verbose::Verbose;
+use crate::yasg::YasgClass;
+use crate::yasg::YasgFile;
+use std::path::Path;
+use std::path::PathBuf;
+
+/************************************************************************************************/
+
+#[derive(Debug)]
+pub struct Site {
+    config: SiteConfig,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Default, Clone)]
+pub struct BuildOptions {
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+}
+
+/************************************************************************************************/
+
+impl Site {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Site, YasgError> {
+        // either the directory of the site or its config file
+        let path = path.as_ref();
+        let options = if path.is_file() {
+            SiteOptions {
+                site_dir: path.parent().map(Path::to_path_buf),
+                config_file: path.file_name().map(PathBuf::from),
+                ..SiteOptions::default()
+            }
+        } else {
+            SiteOptions {
+                site_dir: Some(path.to_path_buf()),
+                ..SiteOptions::default()
+            }
+        };
+
+        let mut site = Site::open(&mut quiet(), &options)?;
+        site.config.plugins.load(&mut quiet())?;
+
+        Ok(site)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn open(verbose: &mut Verbose, options: &SiteOptions) -> Result<Site, YasgError> {
+        // like load, with an environment, other directories and everything logged
+        verbose.println(&sr(
+            VerboseReadingSiteConfig,
+            &[options.config_path().to_str().unwrap()],
+        ));
+        verbose.increate_indent();
+        let config = SiteConfig::read_from_yaml(verbose, options);
+        verbose.decrease_indent();
+
+        Ok(Site { config: config? })
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn config(&self) -> &SiteConfig {
+        &self.config
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn build(&self, options: &BuildOptions) -> BuildReport {
+        // nothing is printed, the warnings and errors end up in the report
+        let mut verbose = quiet();
+        let mut report = BuildReport::new();
+        let result = self.build_with(&mut verbose, options, &mut report);
+        report.finish(verbose.warnings(), &result);
+
+        report
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn build_with(
+        &self,
+        verbose: &mut Verbose,
+        options: &BuildOptions,
+        report: &mut BuildReport,
+    ) -> Result<(), YasgError> {
+        // the loaded config is built as it is, only the directories can be changed
+        let mut config = self.config.clone();
+        config.override_paths(options.input.as_deref(), options.output.as_deref());
+
+        perform_build(verbose, config, report)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn check(&self, verbose: &mut Verbose) -> Result<CheckReport, YasgError> {
+        // checks the links of the last build, the output directory has to exist
+        perform_check(verbose, &self.config)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn clean(
+        &self,
+        verbose: &mut Verbose,
+        options: &CleanOptions,
+    ) -> Result<Vec<PathBuf>, YasgError> {
+        perform_clean(verbose, &self.config, options)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn render_page<P: AsRef<Path>>(&self, path: P) -> Result<String, YasgError> {
+        // renders a single page, relative to the input directory, without writing anything
+        let config = &self.config;
+        let path = path.as_ref();
+        let relative = path.strip_prefix(&config.input).unwrap_or(path);
+        if !is_relative_inside(relative) {
+            return Err(YasgError::new(sr(
+                ErrorPageOutsideInput,
+                &[&path.to_string_lossy(), &config.input.to_string_lossy()],
+            ))
+            .kind(ErrorKind::Content));
+        }
+
+        let mut verbose = quiet();
+        let file_list = build_file_list(&mut verbose, config)?;
+        let data = load_data_files(&mut verbose, config).map_err(|e| e.kind(ErrorKind::Content))?;
+
+        let mut templates = Templates::default();
+        for template_path in file_list
+            .iter()
+            .filter(|path| !path.starts_with(&config.data) && YasgFile::has_front_matter(path))
+        {
+            let file = YasgFile::parse(config, template_path)?;
+            if file.class() == Some(YasgClass::Template) {
+                templates.add(file);
+            }
+        }
+        templates.prepare(&mut verbose, config)?;
+
+        let page = YasgFile::parse(config, &config.input.join(relative))?;
+        let page_name = page.relative_path().to_str().unwrap();
+        let template = templates.for_page(&page)?.ok_or_else(|| {
+            YasgError::new(sr(ErrorNoTemplateFor, &[page_name])).kind(ErrorKind::Content)
+        })?;
+        page.validate(&mut verbose, config, Some(template))?;
+
+        let admonition = templates.admonition()?;
+        page.render_page(config, template, admonition, &data, &[&page])
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl BuildOptions {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn new() -> BuildOptions {
+        BuildOptions::default()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn input<P: AsRef<Path>>(mut self, input: P) -> BuildOptions {
+        self.input = Some(input.as_ref().to_path_buf());
+        self
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn output<P: AsRef<Path>>(mut self, output: P) -> BuildOptions {
+        self.output = Some(output.as_ref().to_path_buf());
+        self
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn quiet() -> Verbose {
+    let mut verbose = Verbose::new();
+    verbose.set_level(LogLevel::Off);
+
+    verbose
+}
+
+/************************************************************************************************/
@@ -0,0 +1,135 @@
+/************************************************************************************************/
+
+use crate::config::SiteConfig;
+use crate::error::ErrorKind;
+use crate::error::YasgError;
+use crate::text::sr;
+use crate::text::Text::*;
+use crate::verbose::Verbose;
+use crate::yasg::YasgClass;
+use crate::yasg::YasgFile;
+
+/************************************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct Templates {
+    templates: Vec<YasgFile>,
+}
+
+/************************************************************************************************/
+
+impl Templates {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn add(&mut self, template: YasgFile) {
+        self.templates.push(template);
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn prepare(&mut self, verbose: &mut Verbose, config: &SiteConfig) -> Result<(), YasgError> {
+        // the order of the file system must not decide anything, so conflicts are errors
+        self.templates
+            .sort_by(|a, b| a.relative_path().cmp(b.relative_path()));
+
+        for (i, template) in self.templates.iter().enumerate() {
+            for other in self.templates[..i]
+                .iter()
+                .filter(|other| other.for_class() == template.for_class())
+            {
+                let text = if other.template_name() == template.template_name() {
+                    ErrorDuplicateTemplateName
+                } else if other.is_default_template() && template.is_default_template() {
+                    ErrorDuplicateDefaultTemplate
+                } else {
+                    continue;
+                };
+                return Err(YasgError::new(sr(
+                    text,
+                    &[
+                        other.relative_path().to_str().unwrap(),
+                        template.relative_path().to_str().unwrap(),
+                        template.template_name(),
+                    ],
+                ))
+                .kind(ErrorKind::Content));
+            }
+        }
+
+        for template in self.templates.iter_mut() {
+            template.validate(verbose, config, None)?;
+            template.precompile(config)?;
+        }
+
+        Ok(())
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn for_page(&self, page: &YasgFile) -> Result<Option<&YasgFile>, YasgError> {
+        // a page picks a template by name, otherwise it gets the default of its class
+        let class = match page.class() {
+            Some(class) => class,
+            None => return Ok(None),
+        };
+        let page_name = page.relative_path().to_str().unwrap();
+
+        match page.template() {
+            Some(name) => self
+                .of_class(class)
+                .find(|template| template.template_name() == name)
+                .map(Some)
+                .ok_or_else(|| {
+                    YasgError::new(sr(ErrorUnknownTemplate, &[page_name, name, class.name()]))
+                        .kind(ErrorKind::Content)
+                }),
+            None => self.default_for(class).map_err(|e| {
+                e.add(sr(ErrorNoTemplateFor, &[page_name]))
+                    .kind(ErrorKind::Content)
+            }),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn admonition(&self) -> Result<Option<&YasgFile>, YasgError> {
+        self.default_for(YasgClass::Admonition)
+            .map_err(|e| e.kind(ErrorKind::Content))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn default_for(&self, class: YasgClass) -> Result<Option<&YasgFile>, YasgError> {
+        // a single template is the default without saying so
+        let templates: Vec<&YasgFile> = self.of_class(class).collect();
+        if templates.len() < 2 {
+            return Ok(templates.first().copied());
+        }
+
+        match templates
+            .iter()
+            .find(|template| template.is_default_template())
+        {
+            Some(template) => Ok(Some(template)),
+            None => {
+                let names: Vec<&str> = templates.iter().map(|t| t.template_name()).collect();
+                Err(YasgError::new(sr(
+                    ErrorNoDefaultTemplate,
+                    &[class.name(), &names.join(", ")],
+                )))
+            }
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn of_class(&self, class: YasgClass) -> impl Iterator<Item = &YasgFile> {
+        self.templates
+            .iter()
+            .filter(move |template| template.for_class() == Some(class))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
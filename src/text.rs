@@ -1,11 +1,61 @@
 /************************************************************************************************/
 
-pub enum Text {
+use crate::constants::*;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use yaml_rust::YamlLoader;
+
+/************************************************************************************************/
+
+// The catalogs use the names of the variants as keys, English is compiled into s() below and is
+// the fallback for anything missing in a catalog.
+static CATALOGS: &[(&str, &str)] = &[
+    (LOCALE_DE, include_str!("locales/de.yaml")),
+    (LOCALE_NL, include_str!("locales/nl.yaml")),
+];
+
+static CATALOG: OnceLock<HashMap<Text, String>> = OnceLock::new();
+
+/************************************************************************************************/
+
+macro_rules! texts {
+    ($($variant:ident,)*) => {
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        pub enum Text {
+            $($variant,)*
+        }
+
+        impl Text {
+            pub const ALL: &'static [Text] = &[$(Text::$variant,)*];
+        }
+    };
+}
+
+/************************************************************************************************/
+
+texts! {
     /*------------------------------------------------------------------------------------------*/
     CliBuildAbout,
+    CliCheckAbout,
     CliCleanAbout,
     CliVerboseHelp,
     CliSiteHelp,
+    CliSiteDirHelp,
+    CliEnvHelp,
+    CliConfigHelp,
+    CliInputHelp,
+    CliOutputHelp,
+    CliReportHelp,
+    CliQuietHelp,
+    CliLogLevelHelp,
+    CliTimestampsHelp,
+    CliTimingsHelp,
+    CliColorHelp,
+    CliFormatHelp,
+    CliLangHelp,
+    CliJobsHelp,
+    CliDryRunHelp,
+    CliKeepHelp,
     CliNewAbout,
     /*------------------------------------------------------------------------------------------*/
     ErrorYasgExit,
@@ -15,34 +65,175 @@ pub enum Text {
     ErrorOutputDirectoryNotExisting,
     ErrorOutputIsNotDirectory,
     ErrorOutputIsNotEmpty,
+    ErrorPathNotUtf8,
     ErrorWriteLongHelp,
-    ErrorWhileReadingSiteYaml,
+    ErrorCommandNotImplemented,
+    ErrorInvalidArgument,
     ErrorNoValidValueField,
-    ErrorValidatingSiteYaml,
+    ErrorValidatingSiteConfig,
+    ErrorWhileReadingFile,
+    ErrorWriteFile,
+    ErrorBrokenLinksFound,
+    ErrorCompileErrorFor,
+    ErrorInTemplate,
+    ErrorNoTemplateFor,
+    ErrorUnknownTemplate,
+    ErrorNoDefaultTemplate,
+    ErrorDuplicateTemplateName,
+    ErrorDuplicateDefaultTemplate,
+    ErrorDuplicateDataName,
+    ErrorUnknownEnvironment,
+    ErrorWrongValueType,
+    ErrorMissingRequiredField,
+    ErrorInvalidFieldDeclaration,
+    ErrorUnknownLanguage,
+    ErrorDuplicateTranslation,
+    ErrorDuplicateOutput,
+    ErrorPageOutsideInput,
+    ErrorUnknownTemplateEngine,
+    ErrorHookFailed,
+    ErrorHookTimeout,
+    ErrorHookExitStatus,
+    ErrorLoadingPlugin,
+    ErrorPlugin,
+    ErrorPluginOutput,
+    ErrorPluginImport,
+    ErrorDuplicateHelper,
+    ErrorCleanContainsInput,
+    ErrorCleanContainsSiteRoot,
+    ErrorCleanNoBuildMarker,
+    ErrorDeleting,
+    ErrorMathUnterminated,
+    ErrorMathUnbalancedBraces,
+    ErrorMathUnbalancedDelimiters,
+    ErrorMathMissingArgument,
+    ErrorMathUnknownCommand,
+    /*------------------------------------------------------------------------------------------*/
+    WarningMessage,
+    WarningUnknownKey,
+    WarningUnknownKeySuggestion,
+    WarningNameNotUtf8,
+    WarningSymlinkLoop,
+    WarningSymlinkDangling,
+    WarningSpecialFile,
     /*------------------------------------------------------------------------------------------*/
     VerboseBuilding,
+    VerboseApplyingEnvironment,
     VerboseDone,
+    VerbosePhaseTiming,
+    VerboseResolvedPaths,
+    VerboseUsingTemplate,
     VerboseReadingSiteConfig,
     VerboseBuildingFileList,
+    VerboseLoadingDataFiles,
+    VerboseLoadingData,
+    VerboseIgnoring,
+    VerboseExcluding,
+    VerboseSkippingSymlink,
+    VerboseSkippingOutput,
     VerboseProcessingFiles,
     VerboseProcessingPages,
     VerboseCopying,
     VerboseCompiling,
     VerboseCleaning,
     VerboseDeletingDirectory,
+    VerboseDeletingFile,
     VerboseCreatingOutputDirectory,
+    VerboseChecking,
+    VerboseParsingHtml,
+    VerboseParsing,
+    VerboseCheckingLinks,
+    VerboseRunningHook,
+    VerboseLoadingPlugin,
+    VerboseGeneratedPage,
+    /*------------------------------------------------------------------------------------------*/
+    CheckBrokenLink,
+    CheckMissingTarget,
+    CheckMissingFragment,
+    CheckExternalLink,
+    CheckSummary,
+    /*------------------------------------------------------------------------------------------*/
+    CleanWouldRemove,
+    IgnoreDefaultRules,
     /*------------------------------------------------------------------------------------------*/
+    TypeScalar,
+    TypeBoolean,
+    TypeList,
+    TypeMap,
+    TypeDate,
+    TypeEnum,
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+pub fn set_locale(locale: &str) {
+    // only the language matters, 'nl_NL.UTF-8' selects the 'nl' catalog
+    let language = locale
+        .split(['_', '.', '-', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if let Some((_, source)) = CATALOGS.iter().find(|(l, _)| *l == language) {
+        let _ = CATALOG.set(parse_catalog(source));
+    }
+}
+
+/************************************************************************************************/
+
+fn parse_catalog(source: &str) -> HashMap<Text, String> {
+    let mut catalog = HashMap::new();
+    let docs = YamlLoader::load_from_str(source).unwrap_or_default();
+
+    if let Some(doc) = docs.first() {
+        for text in Text::ALL {
+            if let Some(translation) = doc[format!("{:?}", text).as_str()].as_str() {
+                catalog.insert(*text, translation.to_string());
+            }
+        }
+    }
+
+    catalog
 }
 
 /************************************************************************************************/
 
 pub fn s(text: Text) -> &'static str {
+    match CATALOG.get().and_then(|catalog| catalog.get(&text)) {
+        Some(translation) => translation,
+        None => english(text),
+    }
+}
+
+/************************************************************************************************/
+
+fn english(text: Text) -> &'static str {
     match text {
         /*--------------------------------------------------------------------------------------*/
         Text::CliBuildAbout => "Builds the site.",
+        Text::CliCheckAbout => "Checks the generated site for broken links and missing assets.",
         Text::CliCleanAbout => "Cleans up previously generated site.",
-        Text::CliVerboseHelp => "Use verbose output.",
+        Text::CliVerboseHelp => "Use verbose output, use it twice for debug output.",
         Text::CliSiteHelp => "Name of the website directory.",
+        Text::CliEnvHelp => "Environment profile to apply on top of the config file.",
+        Text::CliSiteDirHelp => "Directory of the website. Defaults to the current directory.",
+        Text::CliConfigHelp => {
+            "Config file, relative to the website directory. Defaults to Site.yaml."
+        }
+        Text::CliInputHelp => "Input directory, overrides input-path from the config file.",
+        Text::CliOutputHelp => "Output directory, overrides output-path from the config file.",
+        Text::CliReportHelp => "Writes a JSON build report to the given file.",
+        Text::CliQuietHelp => "Only show errors and warnings.",
+        Text::CliLogLevelHelp => "Log level, overrides --quiet and --verbose.",
+        Text::CliTimestampsHelp => "Prefixes log lines with the time since the start.",
+        Text::CliTimingsHelp => "Shows how long every phase took.",
+        Text::CliColorHelp => "Colored log output, 'auto' only colors terminals.",
+        Text::CliFormatHelp => "Output format, 'json' prints the build report instead of text.",
+        Text::CliLangHelp => "Language of the messages, e.g. 'nl' or 'de'. Defaults to LANG.",
+        Text::CliJobsHelp => "Number of worker threads. Defaults to the number of CPUs.",
+        Text::CliDryRunHelp => "Only lists what would be removed.",
+        Text::CliKeepHelp => "Keeps files matching the pattern, e.g. CNAME or '.git'.",
         Text::CliNewAbout => {
             "Creates a new directory and initialises YASG content and config files."
         }
@@ -54,22 +245,121 @@ pub fn s(text: Text) -> &'static str {
         Text::ErrorOutputDirectoryNotExisting => "Output directory '{1}' does not exist.",
         Text::ErrorOutputIsNotDirectory => "Output '{1}' is not a directory.",
         Text::ErrorOutputIsNotEmpty => "Output directory '{1}' is not empty.",
+        Text::ErrorPathNotUtf8 => "Path {1} is not valid UTF-8.",
         Text::ErrorWriteLongHelp => "An error occured while writing the help information.",
-        Text::ErrorWhileReadingSiteYaml => "An error occured while reading Site.yaml.",
+        Text::ErrorCommandNotImplemented => "The {1} command is not implemented yet.",
+        Text::ErrorInvalidArgument => "'{1}' is not a valid value for --{2}.",
         Text::ErrorNoValidValueField => "No valid value has been provided for the '{1}' field.",
-        Text::ErrorValidatingSiteYaml => "Site.yaml contains invalid information.",
+        Text::ErrorValidatingSiteConfig => "{1} contains invalid information.",
+        Text::ErrorWhileReadingFile => "An error occured while reading {1}.",
+        Text::ErrorWriteFile => "An error occured while writing {1}.",
+        Text::ErrorBrokenLinksFound => "{1} broken link(s) found.",
+        Text::ErrorCompileErrorFor => "Compile error for {1}.",
+        Text::ErrorInTemplate => "Error in template {1}.",
+        Text::ErrorNoTemplateFor => "No template found for the class of {1}.",
+        Text::ErrorUnknownTemplate => {
+            "Page {1} asks for template '{2}', but class '{3}' has no template with that name."
+        }
+        Text::ErrorNoDefaultTemplate => {
+            "Class '{1}' has the templates {2}, but none of them is marked as the default."
+        }
+        Text::ErrorDuplicateTemplateName => {
+            "Templates {1} and {2} are both named '{3}' for the same class."
+        }
+        Text::ErrorDuplicateDefaultTemplate => {
+            "Templates {1} and {2} are both marked as the default for the same class."
+        }
+        Text::ErrorWrongValueType => "{1}:{2}:{3}: The value of '{4}' should be {5}.",
+        Text::ErrorMissingRequiredField => "{1}: The required field '{2}' is missing.",
+        Text::ErrorInvalidFieldDeclaration => {
+            "Invalid declaration of field '{1}', expected a type of string, date, list or enum."
+        }
+        Text::ErrorUnknownLanguage => "Language '{1}' is not declared in the site configuration.",
+        Text::ErrorDuplicateTranslation => "{1} and {2} are both the '{3}' translation of a page.",
+        Text::ErrorDuplicateOutput => "{1} and {2} would both be written to {3}.",
+        Text::ErrorPageOutsideInput => "{1} is not a page inside the input directory {2}.",
+        Text::ErrorUnknownTemplateEngine => {
+            "Unknown template engine '{1}', expected mustache, jinja or handlebars."
+        }
+        Text::ErrorHookFailed => "The {1} hook '{2}' failed.",
+        Text::ErrorHookTimeout => "It did not finish within {1} second(s).",
+        Text::ErrorHookExitStatus => "It ended with {1}.",
+        Text::ErrorLoadingPlugin => "Could not load plugin {1}.",
+        Text::ErrorPlugin => "Plugin {1} failed in {2}.",
+        Text::ErrorPluginOutput => "Plugin {1} returned invalid output from {2}.",
+        Text::ErrorDuplicateHelper => "Plugins {1} and {2} both provide the template helper {3}.",
+        Text::ErrorPluginImport => {
+            "It imports {1}, but plugins have no access to anything outside."
+        }
+        Text::ErrorCleanContainsInput => "Refusing to clean {1}, it contains the input directory.",
+        Text::ErrorCleanContainsSiteRoot => "Refusing to clean {1}, it contains the site root.",
+        Text::ErrorCleanNoBuildMarker => {
+            "Refusing to clean {1}, it has no {2} file and was not created by a build."
+        }
+        Text::ErrorDeleting => "An error occured while deleting {1}.",
+        Text::ErrorUnknownEnvironment => "No configuration found for environment '{1}'.",
+        Text::ErrorDuplicateDataName => "Data file {1} uses a name that is already taken.",
+        Text::ErrorMathUnterminated => "Display math '{1}' is not terminated with '$$'.",
+        Text::ErrorMathUnbalancedBraces => "Unbalanced braces in math '{1}'.",
+        Text::ErrorMathUnbalancedDelimiters => "Unbalanced \\left and \\right in math '{1}'.",
+        Text::ErrorMathMissingArgument => "Missing argument for '{1}' in math '{2}'.",
+        Text::ErrorMathUnknownCommand => "Unknown command '{1}' in math '{2}'.",
+        /*--------------------------------------------------------------------------------------*/
+        Text::WarningMessage => "Warning: {1}",
+        Text::WarningUnknownKey => "{1}:{2}:{3}: Unknown key '{4}'.",
+        Text::WarningUnknownKeySuggestion => "{1}:{2}:{3}: Unknown key '{4}', did you mean '{5}'?",
+        Text::WarningNameNotUtf8 => "Skipping {1}, its name is not valid UTF-8.",
+        Text::WarningSymlinkLoop => "Skipping {1}, following it would loop back to {2}.",
+        Text::WarningSymlinkDangling => "Skipping {1}, the symbolic link points to nothing.",
+        Text::WarningSpecialFile => "Skipping {1}, it is neither a file nor a directory.",
         /*--------------------------------------------------------------------------------------*/
         Text::VerboseBuilding => "Building...",
+        Text::VerboseApplyingEnvironment => "Applying environment '{1}' from {2}.",
         Text::VerboseDone => "Done!",
-        Text::VerboseReadingSiteConfig => "Reading site configuration from Site.yaml.",
+        Text::VerbosePhaseTiming => "{1}: {2} ms.",
+        Text::VerboseResolvedPaths => "Input directory is {1}, output directory is {2}.",
+        Text::VerboseUsingTemplate => "Using template {1}.",
+        Text::VerboseReadingSiteConfig => "Reading site configuration from {1}.",
         Text::VerboseBuildingFileList => "Building file list.",
+        Text::VerboseLoadingDataFiles => "Loading data files.",
+        Text::VerboseLoadingData => "Loading {1}.",
+        Text::VerboseIgnoring => "Ignoring {1}.",
+        Text::VerboseExcluding => "Excluding {1}, it matches '{2}' from {3}.",
+        Text::VerboseSkippingSymlink => "Skipping symbolic link {1}.",
+        Text::VerboseSkippingOutput => "Skipping {1}, it is the output directory.",
         Text::VerboseProcessingFiles => "Processing files.",
         Text::VerboseProcessingPages => "Processing pages.",
         Text::VerboseCopying => "Copying {1}.",
         Text::VerboseCompiling => "Compiling {1}.",
         Text::VerboseCleaning => "Cleaning...",
         Text::VerboseDeletingDirectory => "Deleting directory {1}.",
+        Text::VerboseDeletingFile => "Deleting file {1}.",
         Text::VerboseCreatingOutputDirectory => "Creating output directory {1}.",
+        Text::VerboseChecking => "Checking...",
+        Text::VerboseParsingHtml => "Parsing HTML files.",
+        Text::VerboseParsing => "Parsing {1}.",
+        Text::VerboseCheckingLinks => "Checking links.",
+        Text::VerboseRunningHook => "Running {1} hook '{2}'.",
+        Text::VerboseLoadingPlugin => "Loading plugin {1}.",
+        Text::VerboseGeneratedPage => "Page {1} was generated by plugin {2}.",
+        /*--------------------------------------------------------------------------------------*/
+        Text::CheckBrokenLink => "Broken: {1} ({2})",
+        Text::CheckMissingTarget => "target does not exist",
+        Text::CheckMissingFragment => "fragment does not exist",
+        Text::CheckExternalLink => "External: {1}",
+        Text::CheckSummary => {
+            "Checked {1} HTML file(s), found {2} broken link(s) and {3} external link(s)."
+        }
+        /*--------------------------------------------------------------------------------------*/
+        Text::CleanWouldRemove => "Would remove {1}",
+        Text::IgnoreDefaultRules => "the default rules",
+        /*--------------------------------------------------------------------------------------*/
+        Text::TypeScalar => "a single value",
+        Text::TypeBoolean => "true or false",
+        Text::TypeList => "a list",
+        Text::TypeMap => "a mapping of keys to values",
+        Text::TypeDate => "a date like 2019-12-31",
+        Text::TypeEnum => "one of {1}",
         /*--------------------------------------------------------------------------------------*/
     }
 }
@@ -97,3 +387,45 @@ pub fn sr(text: Text, values: &[&str]) -> String {
 }
 
 /************************************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    /*------------------------------------------------------------------------------------------*/
+
+    use super::*;
+
+    /*------------------------------------------------------------------------------------------*/
+
+    #[test]
+    fn every_text_is_translated_in_every_catalog() {
+        for (locale, source) in CATALOGS {
+            let catalog = parse_catalog(source);
+
+            for text in Text::ALL {
+                let translation = catalog.get(text);
+                assert!(
+                    translation.is_some(),
+                    "{:?} is missing in '{}'",
+                    text,
+                    locale
+                );
+
+                // the placeholders have to survive the translation
+                for i in 1..10 {
+                    let placeholder = format!("{{{}}}", i);
+                    assert_eq!(
+                        english(*text).contains(&placeholder),
+                        translation.unwrap().contains(&placeholder),
+                        "{:?} in '{}' does not match the placeholders",
+                        text,
+                        locale
+                    );
+                }
+            }
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
@@ -1,5 +1,9 @@
 /************************************************************************************************/
 
+use serde_json::Map;
+use serde_json::Value;
+use std::path::Component;
+use std::path::Path;
 use yaml_rust::yaml::Yaml;
 
 /************************************************************************************************/
@@ -15,3 +19,129 @@ pub fn yaml_value_as_string(value: &Yaml) -> Option<String> {
 }
 
 /************************************************************************************************/
+
+pub fn yaml_value_as_bool(value: &Yaml) -> Option<bool> {
+    match value {
+        Yaml::Boolean(b) => Some(*b),
+        _ => None,
+    }
+}
+
+/************************************************************************************************/
+
+pub fn yaml_value_as_string_list(value: &Yaml) -> Option<Vec<String>> {
+    match value {
+        Yaml::Array(a) => a.iter().map(yaml_value_as_string).collect(),
+        _ => None,
+    }
+}
+
+/************************************************************************************************/
+
+pub fn yaml_to_json(value: &Yaml) -> Value {
+    // the template engines all take their data as JSON
+    match value {
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Integer(i) => Value::from(*i),
+        Yaml::Real(s) => match s.parse::<f64>() {
+            Ok(f) => Value::from(f),
+            Err(_) => Value::String(s.clone()),
+        },
+        Yaml::Array(a) => Value::Array(a.iter().map(yaml_to_json).collect()),
+        Yaml::Hash(h) => {
+            let mut map = Map::new();
+            for (key, value) in h {
+                if let Some(key_str) = yaml_value_as_string(key) {
+                    map.insert(key_str, yaml_to_json(value));
+                }
+            }
+            Value::Object(map)
+        }
+        _ => match yaml_value_as_string(value) {
+            Some(s) => Value::String(s),
+            None => Value::Null,
+        },
+    }
+}
+
+/************************************************************************************************/
+
+pub fn yaml_deep_merge(base: &mut Yaml, overlay: &Yaml) {
+    match (base, overlay) {
+        (Yaml::Hash(base_hash), Yaml::Hash(overlay_hash)) => {
+            for (key, value) in overlay_hash {
+                match base_hash.get_mut(key) {
+                    Some(base_value) => yaml_deep_merge(base_value, value),
+                    None => {
+                        base_hash.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/************************************************************************************************/
+
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    // Levenshtein distance, only used for 'did you mean' suggestions
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+
+    row[b.len()]
+}
+
+/************************************************************************************************/
+
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    // '*' matches any run of characters and '?' a single one, nothing more is needed for paths
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, t));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/************************************************************************************************/
+
+pub fn is_relative_inside(path: &Path) -> bool {
+    // a non-empty relative path that cannot leave the directory it is relative to
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/************************************************************************************************/
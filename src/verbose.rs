@@ -1,8 +1,56 @@
 /************************************************************************************************/
 
+use crate::constants::*;
+use crate::text::sr;
+use crate::text::Text::*;
+use std::io::stderr;
+use std::io::IsTerminal;
+use std::time::Instant;
+
+/************************************************************************************************/
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+/************************************************************************************************/
+
 pub struct Verbose {
-    enabled: bool,
+    level: LogLevel,
     indent: usize,
+    color: bool,
+    timestamps: bool,
+    timings: bool,
+    started: Instant,
+    last_line: String,
+    phases: Vec<(String, Instant)>,
+    warnings: Vec<String>,
+}
+
+/************************************************************************************************/
+
+impl Default for Verbose {
+    /*------------------------------------------------------------------------------------------*/
+
+    fn default() -> Verbose {
+        Verbose::new()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
 }
 
 /************************************************************************************************/
@@ -12,32 +60,51 @@ impl Verbose {
 
     pub fn new() -> Verbose {
         Verbose {
-            enabled: false,
+            level: LogLevel::Warn,
             indent: 0,
+            color: false,
+            timestamps: false,
+            timings: false,
+            started: Instant::now(),
+            last_line: String::new(),
+            phases: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    pub fn enable(&mut self) {
-        self.enabled = true;
+    pub fn set_level(&mut self, level: LogLevel) {
+        self.level = level;
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    pub fn _disable(&mut self) {
-        self.enabled = false;
+    pub fn set_color(&mut self, mode: ColorMode) {
+        self.color = match mode {
+            ColorMode::Auto => stderr().is_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        };
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    pub fn _is_enabled(&self) -> bool {
-        self.enabled
+    pub fn set_timestamps(&mut self, timestamps: bool) {
+        self.timestamps = timestamps;
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn set_timings(&mut self, timings: bool) {
+        self.timings = timings;
     }
 
     /*------------------------------------------------------------------------------------------*/
 
     pub fn increate_indent(&mut self) {
+        // every indented block is a phase started by the line printed just before it
+        self.phases.push((self.last_line.clone(), Instant::now()));
         self.indent += 1;
     }
 
@@ -47,14 +114,139 @@ impl Verbose {
         if self.indent > 0 {
             self.indent -= 1;
         }
+
+        if let Some((line, started)) = self.phases.pop() {
+            if self.timings {
+                let millis = started.elapsed().as_millis().to_string();
+                let phase = line.trim_end_matches(['.', '!']);
+                self.log(
+                    LogLevel::Warn,
+                    &sr(VerbosePhaseTiming, &[phase, &millis]),
+                    None,
+                );
+            }
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn println(&mut self, line: &str) {
+        self.last_line = line.to_string();
+        self.log(LogLevel::Info, line, None);
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn debug(&mut self, line: &str) {
+        self.log(LogLevel::Debug, line, Some(COLOR_DEBUG));
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn warning(&mut self, line: &str) {
+        // warnings are always shown, only a printer that is switched off leaves them out
+        self.log(
+            LogLevel::Error,
+            &sr(WarningMessage, &[line]),
+            Some(COLOR_WARNING),
+        );
+        self.warnings.push(line.to_string());
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn log(&self, level: LogLevel, line: &str, color: Option<&str>) {
+        if level > self.level {
+            return;
+        }
+
+        let mut output = String::new();
+        if self.timestamps {
+            let elapsed = self.started.elapsed();
+            output.push_str(&format!(
+                "[{:>4}.{:03}s] ",
+                elapsed.as_secs(),
+                elapsed.subsec_millis()
+            ));
+        }
+        // without the surrounding info lines the indentation would only be confusing
+        if self.level >= LogLevel::Info {
+            output.push_str(&"  ".repeat(self.indent));
+        }
+
+        match color {
+            Some(color) if self.color => {
+                output.push_str(&format!("{}{}{}", color, line, COLOR_RESET));
+            }
+            _ => output.push_str(line),
+        }
+
+        // stdout is kept free for the output of the commands themselves
+        eprintln!("{}", output);
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl LogLevel {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub const NAMES: &'static [&'static str] = &[
+        LOG_LEVEL_ERROR,
+        LOG_LEVEL_WARN,
+        LOG_LEVEL_INFO,
+        LOG_LEVEL_DEBUG,
+    ];
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn from(s: &str) -> Option<LogLevel> {
+        match s {
+            LOG_LEVEL_ERROR => Some(LogLevel::Error),
+            LOG_LEVEL_WARN => Some(LogLevel::Warn),
+            LOG_LEVEL_INFO => Some(LogLevel::Info),
+            LOG_LEVEL_DEBUG => Some(LogLevel::Debug),
+            _ => None,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl ColorMode {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub const NAMES: &'static [&'static str] =
+        &[COLOR_MODE_AUTO, COLOR_MODE_ALWAYS, COLOR_MODE_NEVER];
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorMode::Auto => COLOR_MODE_AUTO,
+            ColorMode::Always => COLOR_MODE_ALWAYS,
+            ColorMode::Never => COLOR_MODE_NEVER,
+        }
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    pub fn println(&self, line: &str) {
-        if self.enabled {
-            let indent = "  ".repeat(self.indent);
-            println!("{}{}", indent, line);
+    pub fn from(s: &str) -> Option<ColorMode> {
+        match s {
+            COLOR_MODE_AUTO => Some(ColorMode::Auto),
+            COLOR_MODE_ALWAYS => Some(ColorMode::Always),
+            COLOR_MODE_NEVER => Some(ColorMode::Never),
+            _ => None,
         }
     }
 
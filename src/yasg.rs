@@ -1,36 +1,84 @@
 /************************************************************************************************/
 
+use crate::config::MarkdownOptions;
 use crate::config::SiteConfig;
 use crate::constants::*;
+use crate::data::json_to_yaml;
+use crate::data::toml_to_yaml;
+use crate::engine::CompiledTemplate;
+use crate::engine::TemplateEngine;
+use crate::error::ErrorKind;
 use crate::error::YasgError;
+use crate::math::render_math_in_markdown;
+use crate::plugin::Plugins;
+use crate::schema::Diagnostics;
+use crate::schema::FieldDeclaration;
+use crate::schema::FRONT_MATTER_SCHEMA;
 use crate::text::sr;
 use crate::text::Text::*;
+use crate::util::yaml_to_json;
+use crate::util::yaml_value_as_bool;
 use crate::util::yaml_value_as_string;
-use mustache::Data;
-use mustache::MapBuilder;
+use crate::util::yaml_value_as_string_list;
+use crate::verbose::Verbose;
 use pulldown_cmark::html;
+use pulldown_cmark::Event;
 use pulldown_cmark::Options;
 use pulldown_cmark::Parser;
+use pulldown_cmark::Tag;
+use serde_json::json;
+use serde_json::Value;
+use std::fs::create_dir_all;
+use std::fs::read_to_string;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::OnceLock;
+use yaml_rust::Yaml;
 use yaml_rust::Yaml::Hash;
 use yaml_rust::YamlLoader;
 
 /************************************************************************************************/
 
+static DEFAULT_ADMONITION: OnceLock<CompiledTemplate> = OnceLock::new();
+
+/************************************************************************************************/
+
 #[derive(Debug)]
 pub struct YasgFile {
     prefix_input_path: PathBuf,
     prefix_output_path: PathBuf,
     relative_path: PathBuf,
     yaml_content: String,
+    front_matter: Yaml,
     body_content: String,
     class: Option<YasgClass>,
     for_class: Option<YasgClass>,
+    template: Option<String>,
+    template_name: Option<String>,
+    default_template: bool,
     title: Option<String>,
     description: Option<String>,
+    markdown: MarkdownOptions,
+    kinds: Vec<String>,
+    fields: Vec<FieldDeclaration>,
+    language: Option<String>,
+    language_prefix: PathBuf,
+    translation_path: PathBuf,
+    engine: Option<TemplateEngine>,
+    compiled_template: Option<CompiledTemplate>,
+    format: YasgFormat,
+}
+
+/************************************************************************************************/
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum YasgFormat {
+    Yasg,
+    Markdown,
+    Html,
 }
 
 /************************************************************************************************/
@@ -39,6 +87,7 @@ pub struct YasgFile {
 pub enum YasgClass {
     Template,
     Page,
+    Admonition,
 }
 
 /************************************************************************************************/
@@ -52,83 +101,200 @@ impl YasgFile {
             prefix_output_path: PathBuf::new(),
             relative_path: PathBuf::new(),
             yaml_content: String::new(),
+            front_matter: Yaml::Null,
             body_content: String::new(),
             class: None,
             for_class: None,
+            template: None,
+            template_name: None,
+            default_template: false,
             title: None,
             description: None,
+            markdown: MarkdownOptions::default(),
+            kinds: Vec::new(),
+            fields: Vec::new(),
+            language: None,
+            language_prefix: PathBuf::new(),
+            translation_path: PathBuf::new(),
+            engine: None,
+            compiled_template: None,
+            format: YasgFormat::Yasg,
         }
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    pub fn parse(config: &SiteConfig, path: &PathBuf) -> Result<YasgFile, YasgError> {
+    pub fn parse(config: &SiteConfig, path: &Path) -> Result<YasgFile, YasgError> {
+        let mut contents = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut contents))
+            .map_err(|e| {
+                YasgError::new(format!("{}", e))
+                    .add(sr(
+                        ErrorWhileReadingFile,
+                        &[config.relative_to_input(path).to_str().unwrap()],
+                    ))
+                    .kind(ErrorKind::Io)
+            })?;
+
+        YasgFile::parse_source(config, path, &contents)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn parse_source(
+        config: &SiteConfig,
+        path: &Path,
+        contents: &str,
+    ) -> Result<YasgFile, YasgError> {
+        // the path does not have to exist, pages generated by plugins only live in memory
         let mut yf = YasgFile::new();
         yf.prefix_input_path = config.input.clone();
         yf.prefix_output_path = config.output.clone();
         yf.relative_path = config.relative_to_input(path);
+        yf.markdown = config.markdown;
+        yf.format = YasgFormat::from_path(path).unwrap_or(YasgFormat::Yasg);
 
-        let f = File::open(yf.full_input_path()).unwrap();
-        let reader = BufReader::new(f);
-        let mut in_body = false;
-
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if in_body {
-                    yf.body_content.push_str(line.as_str());
-                    yf.body_content.push('\n');
-                } else if line.eq("---") {
-                    in_body = true;
-                } else {
-                    yf.yaml_content.push_str(line.as_str());
-                    yf.yaml_content.push('\n');
-                }
-            }
+        match yf
+            .split_front_matter(contents)
+            .and_then(|()| yf.parse_yaml())
+            .map(|()| yf.default_class())
+            .and_then(|()| yf.validate_class())
+            .and_then(|()| yf.resolve_language(config))
+        {
+            Ok(()) => Ok(yf),
+            Err(e) => Err(e
+                .add(sr(
+                    ErrorParseErrorFor,
+                    &[yf.relative_path.to_str().unwrap()],
+                ))
+                .kind(ErrorKind::Content)),
         }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
 
-        yf.parse_yaml();
+    fn split_front_matter(&mut self, contents: &str) -> Result<(), YasgError> {
+        // every kind of front matter ends up as the same YAML document
+        let first_line = contents.lines().next().unwrap_or_default();
+        let rest = contents.split_once('\n').map_or("", |(_, rest)| rest);
 
-        match yf.validate() {
-            Ok(()) => Ok(yf),
-            Err(e) => Err(e.add(sr(
-                ErrorParseErrorFor,
-                &[yf.relative_path.to_str().unwrap()],
-            ))),
+        if first_line == FRONT_MATTER_YAML_DELIMITER {
+            let (front, body) = split_lines(rest, first_line);
+            // the empty first line keeps the positions of the keys in line with the file
+            self.yaml_content = format!("\n{}", front);
+            self.body_content = body;
+            self.front_matter = load_yaml(&self.yaml_content)?;
+        } else if first_line == FRONT_MATTER_TOML_DELIMITER {
+            let (front, body) = split_lines(rest, first_line);
+            let value: toml::Value =
+                toml::from_str(&front).map_err(|e| YasgError::new(format!("{}", e)))?;
+            self.body_content = body;
+            self.front_matter = toml_to_yaml(value);
+        } else if let Some((value, end)) = json_front_matter(contents) {
+            // the JSON object ends where the parser stops, the body starts on the next line
+            let rest = &contents[end..];
+            self.body_content = match rest.find('\n') {
+                Some(i) => rest[i + 1..].to_string(),
+                None => String::new(),
+            };
+            self.front_matter = json_to_yaml(value);
+        } else if self.format == YasgFormat::Yasg {
+            // the original format, everything before the first delimiter line is YAML
+            let (front, body) = split_lines(contents, FRONT_MATTER_YAML_DELIMITER);
+            self.yaml_content = front;
+            self.body_content = body;
+            self.front_matter = load_yaml(&self.yaml_content)?;
+        } else {
+            self.body_content = contents.to_string();
         }
+
+        Ok(())
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn parse_yaml(&mut self) {
-        let docs = YamlLoader::load_from_str(self.yaml_content.as_str()).unwrap();
-        if !docs.is_empty() {
-            let doc = docs.first().unwrap();
+    fn parse_yaml(&mut self) -> Result<(), YasgError> {
+        let doc = self.front_matter.clone();
+        if let Hash(h) = &doc {
+            for (key, value) in h {
+                if let Some(key_str) = key.as_str() {
+                    if key_str == YAML_CLASS {
+                        if let Some(s) = yaml_value_as_string(value) {
+                            self.class = YasgClass::from(&s)
+                        }
+                    } else if key_str == YAML_TITLE {
+                        self.title = yaml_value_as_string(value);
+                    } else if key_str == YAML_DESCRIPTION {
+                        self.description = yaml_value_as_string(value);
+                    } else if key_str == YAML_FOR_CLASS {
+                        if let Some(s) = yaml_value_as_string(value) {
+                            self.for_class = YasgClass::from(&s)
+                        }
+                    } else if key_str == YAML_TEMPLATE {
+                        self.template = yaml_value_as_string(value);
+                    } else if key_str == YAML_NAME {
+                        self.template_name = yaml_value_as_string(value);
+                    } else if key_str == YAML_DEFAULT {
+                        self.default_template = yaml_value_as_bool(value).ok_or_else(|| {
+                            YasgError::new(sr(ErrorNoValidValueField, &[YAML_DEFAULT]))
+                        })?;
+                    } else if key_str == YAML_MARKDOWN {
+                        self.markdown.parse_yaml(value)?;
+                    } else if key_str == YAML_KINDS {
+                        self.kinds = yaml_value_as_string_list(value).ok_or_else(|| {
+                            YasgError::new(sr(ErrorNoValidValueField, &[YAML_KINDS]))
+                        })?;
+                    } else if key_str == YAML_FIELDS {
+                        self.fields = FieldDeclaration::parse_yaml(value)?;
+                    } else if key_str == YAML_LANG {
+                        self.language = yaml_value_as_string(value);
+                    } else if key_str == YAML_TEMPLATE_ENGINE {
+                        if let Some(s) = yaml_value_as_string(value) {
+                            self.engine = Some(TemplateEngine::parse(&s)?);
+                        }
+                    };
+                } // if let Some
+            } // for (key, value)
+        } // if let Hash
 
-            if let Hash(h) = doc {
-                for (key, value) in h {
-                    if let Some(key_str) = key.as_str() {
-                        if key_str == YAML_CLASS {
-                            if let Some(s) = yaml_value_as_string(value) {
-                                self.class = YasgClass::from(&s)
-                            }
-                        } else if key_str == YAML_TITLE {
-                            self.title = yaml_value_as_string(value);
-                        } else if key_str == YAML_DESCRIPTION {
-                            self.description = yaml_value_as_string(value);
-                        } else if key_str == YAML_FOR_CLASS {
-                            if let Some(s) = yaml_value_as_string(value) {
-                                self.for_class = YasgClass::from(&s)
-                            }
-                        };
-                    } // if let Some
-                } // for (key, value)
-            } // if let Hash
-        } // if !docs.is_empty
+        Ok(())
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn validate(&self) -> Result<(), YasgError> {
+    pub fn has_front_matter(path: &Path) -> bool {
+        // Markdown and HTML files without front matter are plain assets
+        match YasgFormat::from_path(path) {
+            Some(YasgFormat::Yasg) => true,
+            Some(_) => match File::open(path)
+                .ok()
+                .and_then(|f| BufReader::new(f).lines().next())
+                .and_then(Result::ok)
+            {
+                Some(line) if line.starts_with('{') => read_to_string(path)
+                    .is_ok_and(|contents| json_front_matter(&contents).is_some()),
+                Some(line) => {
+                    line == FRONT_MATTER_YAML_DELIMITER || line == FRONT_MATTER_TOML_DELIMITER
+                }
+                None => false,
+            },
+            None => false,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn default_class(&mut self) {
+        // existing Markdown and HTML documents rarely say what they are
+        if self.class.is_none() && self.format != YasgFormat::Yasg {
+            self.class = Some(YasgClass::Page);
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn validate_class(&self) -> Result<(), YasgError> {
         if self.class.is_none() {
             return Err(YasgError::new(sr(ErrorNoValidValueField, &[YAML_CLASS])));
         }
@@ -142,43 +308,128 @@ impl YasgFile {
                     )));
                 }
             }
-            YasgClass::Page => {
-                if self.title.is_none() {
-                    return Err(YasgError::new(sr(ErrorNoValidValueField, &[YAML_TITLE])));
-                }
-                if self.description.is_none() {
-                    return Err(YasgError::new(sr(
-                        ErrorNoValidValueField,
-                        &[YAML_DESCRIPTION],
-                    )));
+            YasgClass::Page => {}
+            YasgClass::Admonition => {
+                // admonitions only exist as templates, never as files of their own
+                return Err(YasgError::new(sr(ErrorNoValidValueField, &[YAML_CLASS])));
+            }
+        }
+
+        Ok(())
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn resolve_language(&mut self, config: &SiteConfig) -> Result<(), YasgError> {
+        self.translation_path = self.relative_path.clone();
+        if config.languages.is_empty() {
+            return Ok(());
+        }
+
+        // 'about.nl.yasg' is the Dutch translation of 'about.yasg'
+        let stem = self.relative_path.file_stem().unwrap().to_str().unwrap();
+        if let (Some((name, code)), Some(extension)) =
+            (stem.rsplit_once('.'), self.relative_path.extension())
+        {
+            if config.language(code).is_some() {
+                let extension = extension.to_str().unwrap();
+                self.translation_path
+                    .set_file_name(format!("{}.{}", name, extension));
+                if self.language.is_none() {
+                    self.language = Some(code.to_string());
                 }
             }
         }
 
+        let language = match &self.language {
+            Some(code) => config
+                .language(code)
+                .ok_or_else(|| YasgError::new(sr(ErrorUnknownLanguage, &[code])))?,
+            None => &config.languages[0],
+        };
+        self.language = Some(language.code.clone());
+        self.language_prefix = PathBuf::from(&language.prefix);
+
         Ok(())
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn full_input_path(&self) -> PathBuf {
-        let mut full_path = self.prefix_input_path.clone();
-        full_path.push(&self.relative_path);
+    pub fn validate(
+        &self,
+        verbose: &mut Verbose,
+        config: &SiteConfig,
+        template: Option<&YasgFile>,
+    ) -> Result<(), YasgError> {
+        // the fields declared by the template of the class come on top of the built-in ones
+        let declarations = match template {
+            Some(template) => template.fields.as_slice(),
+            None => &[],
+        };
+        let file_name = self.relative_path.to_str().unwrap();
+        // only YAML front matter has a source to take the positions of the keys from
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.validate(
+            file_name,
+            &self.yaml_content,
+            &self.front_matter,
+            FRONT_MATTER_SCHEMA,
+            declarations,
+        );
+        diagnostics
+            .into_result(verbose, config.strict)
+            .map_err(|e| e.kind(ErrorKind::Content))?;
 
-        full_path
+        if let Some(YasgClass::Page) = self.class {
+            if self.title.is_none() {
+                return Err(YasgError::new(sr(ErrorNoValidValueField, &[YAML_TITLE]))
+                    .add(sr(ErrorParseErrorFor, &[file_name]))
+                    .kind(ErrorKind::Content));
+            }
+            if self.description.is_none() {
+                return Err(
+                    YasgError::new(sr(ErrorNoValidValueField, &[YAML_DESCRIPTION]))
+                        .add(sr(ErrorParseErrorFor, &[file_name]))
+                        .kind(ErrorKind::Content),
+                );
+            }
+        }
+
+        Ok(())
     }
 
     /*------------------------------------------------------------------------------------------*/
 
     fn full_output_path(&self) -> PathBuf {
         let mut full_path = self.prefix_output_path.clone();
-        full_path.push(&self.relative_path);
-        full_path.set_extension(EXTENSION_HTML);
+        full_path.push(self.output_path());
 
         full_path
     }
 
     /*------------------------------------------------------------------------------------------*/
 
+    pub fn output_path(&self) -> PathBuf {
+        let mut output_path = self.language_prefix.join(&self.translation_path);
+        output_path.set_extension(EXTENSION_HTML);
+
+        output_path
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn translation_path(&self) -> &Path {
+        &self.translation_path
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
     pub fn relative_path(&self) -> &PathBuf {
         &self.relative_path
     }
@@ -197,68 +448,482 @@ impl YasgFile {
 
     /*------------------------------------------------------------------------------------------*/
 
-    pub fn compile(&self, config: &SiteConfig, template: &YasgFile) {
-        let mut c_buffer;
+    pub fn template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
 
-        c_buffer = self.compile_body_content_to_html();
+    pub fn template_name(&self) -> &str {
+        // without a name of its own a template is known by its file name
+        match &self.template_name {
+            Some(name) => name,
+            None => self
+                .relative_path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or_default(),
+        }
+    }
 
-        c_buffer = self.compile_template(config, template, c_buffer);
+    /*------------------------------------------------------------------------------------------*/
 
-        self.write_output(c_buffer.as_bytes());
+    pub fn is_default_template(&self) -> bool {
+        self.default_template
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn compile_body_content_to_html(&self) -> String {
+    pub fn compile(
+        &self,
+        config: &SiteConfig,
+        template: &YasgFile,
+        admonition: Option<&YasgFile>,
+        data: &Yaml,
+        translations: &[&YasgFile],
+    ) -> Result<(), YasgError> {
+        let html = self.render_page(config, template, admonition, data, translations)?;
+
+        self.write_output(html.as_bytes())
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn render_page(
+        &self,
+        config: &SiteConfig,
+        template: &YasgFile,
+        admonition: Option<&YasgFile>,
+        data: &Yaml,
+        translations: &[&YasgFile],
+    ) -> Result<String, YasgError> {
+        let c_buffer;
+
+        let add_page_error = |e: YasgError| {
+            e.add(sr(
+                ErrorCompileErrorFor,
+                &[self.relative_path.to_str().unwrap()],
+            ))
+            .kind(ErrorKind::Content)
+        };
+
+        let plugins = &config.plugins;
+        let plugin_page = self.plugin_data();
+
+        let summary;
+        if self.format == YasgFormat::Html {
+            // raw HTML pages only go through the template
+            c_buffer = plugins
+                .transform_html(&plugin_page, self.body_content.clone())
+                .map_err(add_page_error)?;
+            summary = YasgFile::html_summary(&self.body_content);
+        } else {
+            let body = plugins
+                .transform_markdown(&plugin_page, self.body_content.clone())
+                .map_err(add_page_error)?;
+            let markdown =
+                YasgFile::markdown_source(self.markdown, &body).map_err(add_page_error)?;
+
+            let html = self
+                .compile_body_content_to_html(config, &markdown, admonition)
+                .map_err(add_page_error)?;
+            c_buffer = plugins
+                .transform_html(&plugin_page, html)
+                .map_err(add_page_error)?;
+
+            summary = YasgFile::compile_summary(config, &markdown, self.markdown, admonition)
+                .map_err(add_page_error)?;
+        }
+
+        let plugin_data = plugins
+            .template_data(&plugin_page)
+            .map_err(add_page_error)?;
+        let mut data = self.build_data(config, data, translations, summary, c_buffer);
+        if let Some(plugin_data) = plugin_data {
+            data[PLACEHOLDER_PLUGINS] = plugin_data;
+        }
+
+        template.render(config, &data).map_err(add_page_error)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn plugin_data(&self) -> Value {
+        // what plugins get to see of a page, the transforms add the content they work on
+        json!({
+            PLUGIN_KEY_PATH: self.relative_path.to_str().unwrap(),
+            PLUGIN_KEY_FRONT_MATTER: yaml_to_json(&self.front_matter),
+        })
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn markdown_source(options: MarkdownOptions, body: &str) -> Result<String, YasgError> {
+        if options.math {
+            render_math_in_markdown(body)
+        } else {
+            Ok(body.to_string())
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn markdown_options() -> Options {
         let mut options = Options::empty();
         options.insert(Options::ENABLE_TABLES);
         options.insert(Options::ENABLE_FOOTNOTES);
         options.insert(Options::ENABLE_STRIKETHROUGH);
         options.insert(Options::ENABLE_TASKLISTS);
-        let parser = Parser::new_ext(&self.body_content, options);
+
+        options
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn compile_body_content_to_html(
+        &self,
+        config: &SiteConfig,
+        markdown: &str,
+        admonition: Option<&YasgFile>,
+    ) -> Result<String, YasgError> {
+        let events = YasgFile::markdown_events(config, markdown, self.markdown, admonition)?;
 
         let mut output_buffer = String::new();
-        html::push_html(&mut output_buffer, parser);
+        html::push_html(&mut output_buffer, events.into_iter());
 
-        output_buffer
+        Ok(output_buffer)
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn compile_template(
-        &self,
+    fn markdown_events<'a>(
         config: &SiteConfig,
-        template: &YasgFile,
-        page_body: String,
-    ) -> String {
-        let mustache_template = mustache::compile_str(&template.body_content).unwrap(); // FIXME: unwrap
-        let data = self.build_data(config, page_body);
+        markdown: &'a str,
+        options: MarkdownOptions,
+        admonition: Option<&YasgFile>,
+    ) -> Result<Vec<Event<'a>>, YasgError> {
+        let mut parser = Parser::new_ext(markdown, YasgFile::markdown_options());
+        let mut events = Vec::new();
 
-        let output_buffer = mustache_template.render_data_to_string(&data).unwrap(); // FIXME: unwrap
+        while let Some(event) = parser.next() {
+            let kind_and_title = match &event {
+                Event::Start(Tag::CodeBlock(info)) => YasgFile::admonition_info(info, admonition),
+                _ => None,
+            };
 
-        output_buffer
+            if let Some((kind, title)) = kind_and_title {
+                let mut content = String::new();
+                for inner in &mut parser {
+                    match inner {
+                        Event::End(Tag::CodeBlock(_)) => break,
+                        Event::Text(text) => content.push_str(&text),
+                        _ => {}
+                    }
+                }
+
+                let html = YasgFile::compile_admonition(
+                    config, &kind, &title, &content, options, admonition,
+                )?;
+                events.push(Event::Html(html.into()));
+            } else {
+                events.push(event);
+            }
+        }
+
+        Ok(events)
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn admonition_info(info: &str, admonition: Option<&YasgFile>) -> Option<(String, String)> {
+        let info = info.trim();
+        let (kind, title) = match info.find(char::is_whitespace) {
+            Some(pos) => (&info[..pos], info[pos..].trim()),
+            None => (info, ""),
+        };
+
+        let is_kind = match admonition {
+            Some(template) if !template.kinds.is_empty() => {
+                template.kinds.iter().any(|k| k == kind)
+            }
+            _ => DEFAULT_ADMONITION_KINDS.contains(&kind),
+        };
+
+        if is_kind {
+            let title = title.trim_matches('"');
+            Some((kind.to_string(), title.to_string()))
+        } else {
+            None
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn compile_admonition(
+        config: &SiteConfig,
+        kind: &str,
+        title: &str,
+        content: &str,
+        options: MarkdownOptions,
+        admonition: Option<&YasgFile>,
+    ) -> Result<String, YasgError> {
+        // the body is a code block to the page, so math is only rendered here
+        let content = YasgFile::markdown_source(options, content)?;
+        let mut body = String::new();
+        html::push_html(
+            &mut body,
+            YasgFile::markdown_events(config, &content, options, admonition)?.into_iter(),
+        );
+
+        let data = json!({
+            PLACEHOLDER_ADMONITION_KIND: kind,
+            PLACEHOLDER_ADMONITION_TITLE: title,
+            PLACEHOLDER_ADMONITION_BODY: body,
+        });
+
+        match admonition {
+            Some(template) => template.render(config, &data),
+            None => {
+                // the built-in template is known to be valid
+                let template = DEFAULT_ADMONITION.get_or_init(|| {
+                    TemplateEngine::Mustache
+                        .compile(
+                            YASG_CLASS_ADMONITION,
+                            DEFAULT_ADMONITION_TEMPLATE,
+                            &Plugins::default(),
+                        )
+                        .unwrap()
+                });
+                template
+                    .render(YASG_CLASS_ADMONITION, &data)
+                    .map_err(|e| e.add(sr(ErrorInTemplate, &[YASG_CLASS_ADMONITION])))
+            }
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn compile_summary(
+        config: &SiteConfig,
+        markdown: &str,
+        options: MarkdownOptions,
+        admonition: Option<&YasgFile>,
+    ) -> Result<(String, String), YasgError> {
+        // everything above the excerpt separator, otherwise only the first paragraph
+        let events: Vec<Event> = match YasgFile::excerpt(markdown) {
+            Some(excerpt) => YasgFile::markdown_events(config, excerpt, options, admonition)?,
+            None => YasgFile::markdown_events(config, markdown, options, admonition)?
+                .into_iter()
+                .skip_while(|event| *event != Event::Start(Tag::Paragraph))
+                .take_while(|event| *event != Event::End(Tag::Paragraph))
+                .chain(Some(Event::End(Tag::Paragraph)))
+                .collect(),
+        };
+
+        let mut summary_text = String::new();
+        for event in &events {
+            match event {
+                Event::Text(text) | Event::Code(text) => summary_text.push_str(text),
+                Event::SoftBreak | Event::HardBreak => summary_text.push(' '),
+                Event::End(Tag::Paragraph) if !summary_text.is_empty() => summary_text.push(' '),
+                _ => {}
+            }
+        }
+
+        let mut summary_html = String::new();
+        if events.len() > 1 {
+            html::push_html(&mut summary_html, events.into_iter());
+        }
+
+        Ok((summary_html, summary_text.trim().to_string()))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn html_summary(body: &str) -> (String, String) {
+        // without Markdown there are no paragraphs to pick from, only the excerpt separator
+        match YasgFile::excerpt(body) {
+            Some(excerpt) => {
+                let text = html_text(excerpt);
+                (
+                    excerpt.trim().to_string(),
+                    text.split_whitespace().collect::<Vec<_>>().join(" "),
+                )
+            }
+            None => (String::new(), String::new()),
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn excerpt(markdown: &str) -> Option<&str> {
+        let mut offset = 0;
+
+        // the lines keep their line endings, so the offset stays right with CRLF as well
+        for line in markdown.split_inclusive('\n') {
+            if line.trim() == EXCERPT_SEPARATOR {
+                return Some(&markdown[..offset]);
+            }
+            offset += line.len();
+        }
+
+        None
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn count_words(&self) -> usize {
+        if self.format == YasgFormat::Html {
+            return html_text(&self.body_content).split_whitespace().count();
+        }
+
+        Parser::new_ext(&self.body_content, YasgFile::markdown_options())
+            .map(|event| match event {
+                Event::Text(text) | Event::Code(text) => text.split_whitespace().count(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn precompile(&mut self, config: &SiteConfig) -> Result<(), YasgError> {
+        // templates are compiled once per build and shared by all pages using them
+        let engine = self.engine.unwrap_or(config.template_engine);
+        let name = self.relative_path.to_str().unwrap();
+        let template = engine
+            .compile(name, &self.body_content, &config.plugins)
+            .map_err(|e| e.add(sr(ErrorInTemplate, &[name])).kind(ErrorKind::Content))?;
+        self.compiled_template = Some(template);
+
+        Ok(())
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn build_data(&self, config: &SiteConfig, page_body: String) -> Data {
-        let site_title = config.title.clone();
+    fn render(&self, config: &SiteConfig, data: &Value) -> Result<String, YasgError> {
+        let template_name = self.relative_path.to_str().unwrap();
+        let compiled;
+        let template = match &self.compiled_template {
+            Some(template) => template,
+            None => {
+                // the same engine precompile would have used
+                compiled = self
+                    .engine
+                    .unwrap_or(config.template_engine)
+                    .compile(template_name, &self.body_content, &config.plugins)
+                    .map_err(|e| e.add(sr(ErrorInTemplate, &[template_name])))?;
+                &compiled
+            }
+        };
+
+        template
+            .render(template_name, data)
+            .map_err(|e| e.add(sr(ErrorInTemplate, &[template_name])))
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+
+    fn build_data(
+        &self,
+        config: &SiteConfig,
+        data: &Yaml,
+        translations: &[&YasgFile],
+        summary: (String, String),
+        page_body: String,
+    ) -> Value {
+        let language = self.language().and_then(|code| config.language(code));
+        let site_title = match language.and_then(|l| l.title.clone()) {
+            Some(title) => title,
+            None => config.title.clone(),
+        };
         let page_title = self.title.clone().unwrap(); // FIXME: unwrap
         let page_description = self.description.clone().unwrap(); // FIXME unwrap
+        let (page_summary, page_summary_text) = summary;
+        let word_count = self.count_words();
+        let reading_time_minutes = word_count.div_ceil(WORDS_PER_MINUTE);
+
+        // every language version of this page, the page itself included
+        let translations: Vec<Value> = translations
+            .iter()
+            .map(|translation| {
+                json!({
+                    PLACEHOLDER_TRANSLATION_LANG: translation.language().unwrap_or_default(),
+                    PLACEHOLDER_TRANSLATION_URL: config.url(&translation.output_path()),
+                    PLACEHOLDER_TRANSLATION_TITLE: translation.title.clone().unwrap_or_default(),
+                    PLACEHOLDER_TRANSLATION_CURRENT:
+                        translation.relative_path == self.relative_path,
+                })
+            })
+            .collect();
+
+        let mut page_data = json!({
+            PLACEHOLDER_SITE_TITLE: site_title,
+            PLACEHOLDER_PAGE_TITLE: page_title,
+            PLACEHOLDER_PAGE_DESCRIPTION: page_description,
+            PLACEHOLDER_PAGE_BODY: page_body,
+            PLACEHOLDER_PAGE_SUMMARY: page_summary,
+            PLACEHOLDER_PAGE_SUMMARY_TEXT: page_summary_text,
+            PLACEHOLDER_PAGE_WORD_COUNT: word_count,
+            PLACEHOLDER_PAGE_READING_TIME_MINUTES: reading_time_minutes,
+            PLACEHOLDER_PAGE_LANG: self.language().unwrap_or_default(),
+            PLACEHOLDER_PAGE_URL: config.url(&self.output_path()),
+            PLACEHOLDER_TRANSLATIONS: translations,
+            PLACEHOLDER_DATA: yaml_to_json(data),
+        });
+
+        if let Value::Object(ref mut map) = page_data {
+            if let Some(language) = language {
+                map.insert(
+                    PLACEHOLDER_STRINGS.to_string(),
+                    yaml_to_json(&language.strings),
+                );
+            }
+
+            let mut site = yaml_to_json(&config.values);
+            if let (Value::Object(ref mut site_map), Some(environment)) =
+                (&mut site, &config.environment)
+            {
+                site_map.insert(
+                    PLACEHOLDER_ENVIRONMENT.to_string(),
+                    Value::String(environment.clone()),
+                );
+            }
+            map.insert(PLACEHOLDER_SITE.to_string(), site);
+        }
 
-        MapBuilder::new()
-            .insert_str(PLACEHOLDER_SITE_TITLE, site_title)
-            .insert_str(PLACEHOLDER_PAGE_TITLE, page_title)
-            .insert_str(PLACEHOLDER_PAGE_DESCRIPTION, page_description)
-            .insert_str(PLACEHOLDER_PAGE_BODY, page_body)
-            .build()
+        page_data
     }
 
     /*------------------------------------------------------------------------------------------*/
 
-    fn write_output(&self, output_buffer: &[u8]) {
-        let mut f = File::create(self.full_output_path()).unwrap(); // FIXME unwrap
-        f.write_all(output_buffer).unwrap(); // FIXME unwrap
+    fn write_output(&self, output_buffer: &[u8]) -> Result<(), YasgError> {
+        let output_path = self.full_output_path();
+        create_dir_all(output_path.parent().unwrap())
+            .and_then(|()| File::create(&output_path))
+            .and_then(|mut f| f.write_all(output_buffer))
+            .map_err(|e| {
+                YasgError::new(format!("{}", e))
+                    .add(sr(ErrorWriteFile, &[output_path.to_str().unwrap()]))
+                    .kind(ErrorKind::Io)
+            })
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+impl YasgFormat {
+    /*------------------------------------------------------------------------------------------*/
+
+    pub fn from_path(path: &Path) -> Option<YasgFormat> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(EXTENSION_YASG) => Some(YasgFormat::Yasg),
+            Some(EXTENSION_MD) | Some(EXTENSION_MARKDOWN) => Some(YasgFormat::Markdown),
+            Some(EXTENSION_HTML) => Some(YasgFormat::Html),
+            _ => None,
+        }
     }
 
     /*------------------------------------------------------------------------------------------*/
@@ -274,12 +939,96 @@ impl YasgClass {
             Some(YasgClass::Template)
         } else if s == YASG_CLASS_PAGE {
             Some(YasgClass::Page)
+        } else if s == YASG_CLASS_ADMONITION {
+            Some(YasgClass::Admonition)
         } else {
             None
         }
     }
 
     /*------------------------------------------------------------------------------------------*/
+
+    pub fn name(self) -> &'static str {
+        match self {
+            YasgClass::Template => YASG_CLASS_TEMPLATE,
+            YasgClass::Page => YASG_CLASS_PAGE,
+            YasgClass::Admonition => YASG_CLASS_ADMONITION,
+        }
+    }
+
+    /*------------------------------------------------------------------------------------------*/
+}
+
+/************************************************************************************************/
+
+fn html_text(html: &str) -> String {
+    // only good enough for word counts and summaries, entities are left alone
+    let mut text = String::new();
+    let mut in_tag = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text
+}
+
+/************************************************************************************************/
+
+fn split_lines(text: &str, delimiter: &str) -> (String, String) {
+    // the first line equal to the delimiter separates the front matter from the body
+    let mut front = String::new();
+    let mut body = String::new();
+    let mut in_body = false;
+
+    for line in text.lines() {
+        if in_body {
+            body.push_str(line);
+            body.push('\n');
+        } else if line == delimiter {
+            in_body = true;
+        } else {
+            front.push_str(line);
+            front.push('\n');
+        }
+    }
+
+    (front, body)
+}
+
+/************************************************************************************************/
+
+fn json_front_matter(contents: &str) -> Option<(serde_json::Value, usize)> {
+    // only an object that parses is front matter, '{{< shortcode >}}' or '{% raw %}' are not
+    if !contents.starts_with('{') {
+        return None;
+    }
+    let mut stream = serde_json::Deserializer::from_str(contents).into_iter();
+    match stream.next() {
+        Some(Ok(value @ serde_json::Value::Object(_))) => Some((value, stream.byte_offset())),
+        _ => None,
+    }
+}
+
+/************************************************************************************************/
+
+fn load_yaml(source: &str) -> Result<Yaml, YasgError> {
+    let mut docs =
+        YamlLoader::load_from_str(source).map_err(|e| YasgError::new(format!("{}", e)))?;
+
+    if docs.is_empty() {
+        Ok(Yaml::Null)
+    } else {
+        Ok(docs.remove(0))
+    }
 }
 
 /************************************************************************************************/